@@ -53,7 +53,14 @@ impl CLI {
                 return Ok(());
             }
         };
-        let results = gengo.analyze(&self.revision);
+        let rev = match gengo.resolve(&self.revision) {
+            Ok(rev) => rev.to_string(),
+            Err(e) => {
+                writeln!(err, "failed to resolve revision: {}", e)?;
+                return Ok(());
+            }
+        };
+        let results = gengo.analyze(&rev);
         let results = match results {
             Ok(results) => results,
             Err(e) => {
@@ -68,14 +75,8 @@ impl CLI {
         let total = summary.total();
         let total = total as f64;
 
-        let summary = {
-            let mut summary: Vec<(_, _)> = summary.iter().collect();
-            summary.sort_by_key(|(_, size)| usize::MAX - *size);
-            summary
-        };
-
-        for (language, size) in summary.iter() {
-            let percentage = (*size * 100) as f64 / total;
+        for (language, size) in summary.sorted_by_size() {
+            let percentage = (size * 100) as f64 / total;
             #[cfg(feature = "color")]
             let color = language.owo_color().unwrap();
             #[cfg(not(feature = "color"))]
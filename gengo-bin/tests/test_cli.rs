@@ -51,5 +51,17 @@ fn test_color_breakdown_javascript_repo() {
     assert_stdout_snapshot!(&["gengo", "-r", "test/javascript", "-R", ROOT, "--breakdown"]);
 }
 
+#[test]
+fn test_bad_revision() {
+    let cli = gengo_bin::cli::try_new_from(&["gengo", "-r", "mian", "-R", ROOT]).unwrap();
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    cli.run(&mut stdout, &mut stderr).unwrap();
+    let stderr = String::from_utf8(stderr).unwrap();
+    assert!(stdout.is_empty());
+    assert!(stderr.starts_with("failed to resolve revision: "));
+    assert!(stderr.contains("mian"));
+}
+
 //
 // TODO Add test_javascript_repo_windows
@@ -1,34 +1,186 @@
 use super::Entry;
+use crate::languages::Category;
+use crate::{BlobEntry, Language};
+use bstr::{BStr, BString, ByteSlice};
 use indexmap::IndexMap;
+use once_cell::sync::OnceCell;
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Debug};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+#[cfg(feature = "git")]
+pub use stream::Stream;
 pub use summary::Iter as SummaryIter;
 pub use summary::Opts as SummaryOpts;
 pub use summary::Summary;
 
+#[cfg(feature = "git")]
+mod stream;
 mod summary;
 
 /// The result of analyzing a repository along with all of its submodules.
-pub struct Analysis(pub(super) crate::Results);
+#[derive(Clone)]
+pub struct Analysis {
+    pub(super) results: crate::Results,
+    /// The id of the tree that was analyzed.
+    pub(super) tree_id: gix_hash::ObjectId,
+    /// The id of the commit that was analyzed, if the revision was a commit.
+    pub(super) commit_id: Option<gix_hash::ObjectId>,
+    /// The commits that submodules were pinned to, by path.
+    pub(super) submodule_commits: IndexMap<BString, gix_hash::ObjectId>,
+    /// The indices of the identified entries, sorted by path, which are
+    /// built on the first lookup.
+    pub(super) by_path: OnceCell<Vec<usize>>,
+}
 
 impl Analysis {
+    /// The id of the tree that was analyzed. Unlike the revision that was
+    /// requested, which might be a branch, this never changes, so it can be
+    /// used as a key for caching results. This is the null id for an
+    /// analysis of the worktree.
+    pub fn revision(&self) -> gix_hash::ObjectId {
+        self.tree_id
+    }
+
+    /// The id of the commit that was analyzed. Returns `None` if the revision
+    /// was resolved to a tree without a commit.
+    pub fn commit(&self) -> Option<gix_hash::ObjectId> {
+        self.commit_id
+    }
+
+    /// The commits that submodules were pinned to, keyed by the path of the
+    /// submodule relative to the root of the repository, in the order they
+    /// were found. Nested submodules are included.
+    pub fn submodule_commits(&self) -> &IndexMap<BString, gix_hash::ObjectId> {
+        &self.submodule_commits
+    }
+
+    /// Iterates over the files that were identified, in the order they were
+    /// analyzed, which is usually the order of the index, but isn't sorted
+    /// across submodules or `extend`ed analyses. Use `iter_sorted` for a
+    /// stable order.
     pub fn iter(&self) -> impl Iterator<Item = (Cow<'_, Path>, &Entry)> + '_ {
-        let results = &self.0;
+        let results = &self.results;
         results.entries.iter().filter_map(|entry| {
             entry.result.as_ref().and_then(|result| {
-                Some((
-                    {
-                        let p = entry.index_entry.path_in(&results.path_storage);
-                        gix::path::try_from_bstr(p).ok()?
-                    },
-                    result,
-                ))
+                Some((gix_path::try_from_bstr(results.path(entry)).ok()?, result))
             })
         })
     }
 
+    /// Like `iter`, but in lexicographic order of the paths' bytes, like the
+    /// order of git. Entries with the same path keep their order.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (Cow<'_, Path>, &Entry)> + '_ {
+        let results = &self.results;
+        let mut entries: Vec<_> = results
+            .entries
+            .iter()
+            .filter(|entry| entry.result.is_some())
+            .collect();
+        entries.sort_by_key(|entry| results.path(entry));
+        entries.into_iter().filter_map(|entry| {
+            entry.result.as_ref().and_then(|result| {
+                Some((gix_path::try_from_bstr(results.path(entry)).ok()?, result))
+            })
+        })
+    }
+
+    /// Adds the entries of another analysis, like one of another worktree,
+    /// after the entries of this analysis. Paths are kept as they are, so a
+    /// path in both analyses has an entry from each. The revision and commit
+    /// of this analysis are kept, and the submodule commits of `other` are
+    /// added.
+    pub fn extend(&mut self, other: Analysis) {
+        self.results.extend_under(BStr::new(""), other.results);
+        self.submodule_commits.extend(other.submodule_commits);
+        self.by_path.take();
+    }
+
+    /// Gets the entry of a single file by its path, relative to the root of
+    /// the repository. Returns `None` if the file wasn't identified.
+    ///
+    /// The first lookup sorts the entries by path, so later lookups only
+    /// search the sorted entries.
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&Entry> {
+        let path = gix_path::to_unix_separators_on_windows(gix_path::into_bstr(path.as_ref()));
+        let results = &self.results;
+        let by_path = self.by_path.get_or_init(|| {
+            let mut by_path: Vec<_> = results
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.result.is_some())
+                .map(|(index, _)| index)
+                .collect();
+            // NOTE The sort is stable, so the first entry with a path is found.
+            by_path.sort_by_key(|index| results.path(&results.entries[*index]));
+            by_path
+        });
+        let start =
+            by_path.partition_point(|index| results.path(&results.entries[*index]) < path.as_ref());
+        let entry = &results.entries[*by_path.get(start)?];
+        if results.path(entry) != path.as_ref() {
+            return None;
+        }
+        entry.result.as_ref()
+    }
+
+    /// The files that were skipped, with the reason why. These files are
+    /// not included in `iter`.
+    pub fn skipped(&self) -> Vec<(BString, SkipReason)> {
+        let results = &self.results;
+        results
+            .entries
+            .iter()
+            .filter_map(|entry| Some((results.path(entry).to_owned(), entry.skipped?)))
+            .collect()
+    }
+
+    /// The `gengo-language` attributes that didn't name a known language,
+    /// which are otherwise ignored.
+    pub fn unresolved_overrides(&self) -> Vec<UnresolvedOverride> {
+        let results = &self.results;
+        results
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let (name, suggestion) = entry.unresolved_language.as_ref()?;
+                Some(UnresolvedOverride {
+                    path: gix_path::try_from_bstr(results.path(entry))
+                        .ok()?
+                        .into_owned(),
+                    name: name.clone(),
+                    suggestion: suggestion.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// The number of files that were identified, which is the number of
+    /// entries yielded by `iter`.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Checks if no files were identified.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Iterates over the entries whose paths are under `prefix`.
+    ///
+    /// The path is compared by components, so `services/api` matches
+    /// `services/api/server.rs`, but not `services/apix/server.rs`.
+    pub fn iter_under<P: AsRef<Path>>(
+        &self,
+        prefix: P,
+    ) -> impl Iterator<Item = (Cow<'_, Path>, &Entry)> + '_ {
+        let prefix = prefix.as_ref().to_path_buf();
+        self.iter()
+            .filter(move |(path, _)| path.starts_with(&prefix))
+    }
+
     /// Summarizes the analysis by language and size. Includes only
     /// the entries that are detectable.
     pub fn summary(&self) -> Summary {
@@ -39,18 +191,146 @@ impl Analysis {
         self.summary_with(opts)
     }
 
+    /// The detectable language with the most bytes, which is what the
+    /// repository is usually said to be written in. Ties are broken by
+    /// preferring popular languages, and then by name. Returns `None` if no
+    /// files are detectable.
+    pub fn primary_language(&self) -> Option<&Language> {
+        let summary = self.summary();
+        let (primary, _) = summary.iter().max_by(|(a, a_size), (b, b_size)| {
+            a_size
+                .cmp(b_size)
+                .then(a.is_popular().cmp(&b.is_popular()))
+                .then_with(|| b.name().cmp(a.name()))
+        })?;
+        self.iter()
+            .map(|(_, entry)| entry.language())
+            .find(|language| *language == primary)
+    }
+
     /// Summarizes the analysis by language and size.
     pub fn summary_with(&self, opts: SummaryOpts) -> Summary {
+        Self::summarize(self.results.entries.iter(), opts)
+    }
+
+    /// Summarizes the entries whose paths are under `prefix`.
+    pub fn summary_under<P: AsRef<Path>>(&self, prefix: P, opts: SummaryOpts) -> Summary {
+        let prefix = prefix.as_ref();
+        let results = &self.results;
+        let entries = results.entries.iter().filter(|entry| {
+            gix_path::try_from_bstr(results.path(entry)).is_ok_and(|path| path.starts_with(prefix))
+        });
+        Self::summarize(entries, opts)
+    }
+
+    /// Summarizes the analysis by language and size, separately for each
+    /// language category. Each summary only includes the languages of its
+    /// category, and categories without any languages are left out.
+    pub fn summary_by_category(&self, opts: SummaryOpts) -> HashMap<Category, Summary> {
+        let opts = SummaryOpts {
+            include_unknown: false,
+            ..opts
+        };
+        let mut categories = HashMap::new();
+        for entry in &self.results.entries {
+            let Some(result) = &entry.result else {
+                continue;
+            };
+            let category = *result.language().category();
+            categories
+                .entry(category)
+                .or_insert_with(Vec::new)
+                .push(entry);
+        }
+        categories
+            .into_iter()
+            .map(|(category, entries)| {
+                let summary = Self::summarize(entries.into_iter(), opts.clone());
+                (category, summary)
+            })
+            .filter(|(_, summary)| !summary.0.is_empty())
+            .collect()
+    }
+
+    /// Summarizes the analysis by language and size, separately for each
+    /// top-level directory, like `services` and `libs` in a monorepo. Each
+    /// summary includes every file under its directory, and the files at
+    /// the root of the repository are summarized under `.`. The directories
+    /// are in the order they were found, and directories without any
+    /// languages are left out.
+    pub fn summary_by_top_level(&self, opts: SummaryOpts) -> IndexMap<String, Summary> {
+        let results = &self.results;
+        let mut directories = IndexMap::new();
+        for entry in &results.entries {
+            let path = results.path(entry);
+            let directory = match path.find_byte(b'/') {
+                Some(i) => path[..i].to_str_lossy().into_owned(),
+                None => String::from("."),
+            };
+            directories
+                .entry(directory)
+                .or_insert_with(Vec::new)
+                .push(entry);
+        }
+        directories
+            .into_iter()
+            .map(|(directory, entries)| {
+                let summary = Self::summarize(entries.into_iter(), opts.clone());
+                (directory, summary)
+            })
+            .filter(|(_, summary)| !summary.0.is_empty())
+            .collect()
+    }
+
+    fn summarize<'a>(entries: impl Iterator<Item = &'a BlobEntry>, opts: SummaryOpts) -> Summary {
         let mut summary = IndexMap::new();
-        let results = &self.0;
-        for entry in results.entries.iter().filter_map(|e| e.result.as_ref()) {
-            if !(opts.all || entry.detectable()) {
+        let mut unidentified = Vec::new();
+        let mut counted = HashSet::new();
+        // NOTE Only the files that are counted are deduplicated, so a copy
+        //      that isn't detectable doesn't hide one that is.
+        let mut is_duplicate = |id| opts.dedup_by_oid && !counted.insert(id);
+        for blob_entry in entries {
+            if let Some(blob) = &blob_entry.unidentified {
+                if opts.include_unknown
+                    && (opts.all || blob.detectable)
+                    && !is_duplicate(blob_entry.id)
+                {
+                    unidentified.push(blob);
+                }
+                continue;
+            }
+            let Some(entry) = &blob_entry.result else {
+                continue;
+            };
+            if !(opts.all || entry.detectable()) || is_duplicate(blob_entry.id) {
                 continue;
             }
             let language = entry.language().clone();
-            *summary.entry(language).or_insert(0) += entry.size();
+            let size = if opts.by_lines {
+                entry.lines()
+            } else if opts.by_chars {
+                entry.chars()
+            } else {
+                entry.size()
+            };
+            *summary.entry(language).or_insert(0) += size;
+        }
+        for blob in unidentified {
+            let language = if blob.binary {
+                Language::binary()
+            } else {
+                Language::unknown()
+            };
+            let size = if opts.by_lines {
+                blob.lines
+            } else if opts.by_chars {
+                blob.chars
+            } else {
+                blob.size
+            };
+            *summary.entry(language).or_insert(0) += size;
         }
-        Summary(summary)
+        opts.apply_thresholds(summary)
     }
 }
 
@@ -60,3 +340,297 @@ impl Debug for Analysis {
         f.debug_map().entries(self.iter()).finish()
     }
 }
+
+/// Why a file was skipped during analysis.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// The path isn't valid UTF-8 on a platform that requires it.
+    NonUtf8Path,
+    /// The analysis was interrupted before the file was analyzed.
+    Interrupted,
+    /// The blob is missing from the object database, like in a partial
+    /// clone.
+    ObjectMissing,
+    /// The file is a symbolic link. Only the files that links target are
+    /// analyzed.
+    Symlink,
+    /// The file is a submodule that is nested more deeply than the maximum
+    /// submodule depth, so its files weren't analyzed.
+    MaxSubmoduleDepth,
+}
+
+/// A `gengo-language` attribute that didn't name a known language.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnresolvedOverride {
+    path: PathBuf,
+    name: String,
+    suggestion: Option<String>,
+}
+
+impl UnresolvedOverride {
+    /// The path of the file that the attribute was specified for.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The value of the attribute.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The name of the closest known language, if any is close enough.
+    pub fn suggestion(&self) -> Option<&str> {
+        self.suggestion.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Analyzers, Results};
+    use std::path::PathBuf;
+
+    impl Analysis {
+        fn from_results(results: Results) -> Self {
+            Self {
+                results,
+                tree_id: gix_hash::ObjectId::null(gix_hash::Kind::Sha1),
+                commit_id: None,
+                submodule_commits: IndexMap::new(),
+                by_path: OnceCell::new(),
+            }
+        }
+    }
+
+    fn analysis(files: &[(&str, &str, usize)]) -> Analysis {
+        let analyzers = Analyzers::default();
+        let mut results = Results::default();
+        for (path, language, size) in files {
+            let entry = Entry {
+                language: analyzers.get(language).unwrap().clone(),
+                size: *size,
+                lines: size / 10,
+                chars: size / 2,
+                detectable: true,
+                generated: false,
+                documentation: false,
+                vendored: false,
+                binary: false,
+                executable: false,
+                untracked: false,
+                attributes: crate::AttributeState::default(),
+                embedded_languages: Vec::new(),
+                detection: crate::Detection::Extension,
+            };
+            results.push(
+                path.as_bytes().into(),
+                gix_hash::ObjectId::null(gix_hash::Kind::Sha1),
+                Some(entry),
+            );
+        }
+        Analysis::from_results(results)
+    }
+
+    #[test]
+    fn test_len() {
+        let mut analysis = analysis(&[("main.rs", "Rust", 10), ("README.md", "Markdown", 5)]);
+        analysis.results.push(
+            "unknown".into(),
+            gix_hash::ObjectId::null(gix_hash::Kind::Sha1),
+            None,
+        );
+        assert_eq!(analysis.len(), 2);
+        assert!(!analysis.is_empty());
+        assert!(Analysis::from_results(Results::default()).is_empty());
+    }
+
+    #[test]
+    fn test_extend() {
+        let other = analysis(&[("README.md", "Markdown", 5), ("main.rs", "Rust", 20)]);
+        let mut combined = analysis(&[("main.rs", "Rust", 10)]);
+        combined.extend(other.clone());
+        let entries: Vec<_> = combined
+            .iter()
+            .map(|(path, entry)| (path.into_owned(), entry.size()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (PathBuf::from("main.rs"), 10),
+                (PathBuf::from("README.md"), 5),
+                (PathBuf::from("main.rs"), 20),
+            ]
+        );
+        assert_eq!(other.len(), 2);
+    }
+
+    #[test]
+    fn test_iter_sorted() {
+        let analysis = analysis(&[
+            ("src/main.rs", "Rust", 10),
+            ("README.md", "Markdown", 5),
+            ("src-tauri/main.rs", "Rust", 20),
+            ("a.rs", "Rust", 1),
+        ]);
+        let paths: Vec<_> = analysis
+            .iter_sorted()
+            .map(|(path, _)| path.into_owned())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("README.md"),
+                PathBuf::from("a.rs"),
+                PathBuf::from("src-tauri/main.rs"),
+                PathBuf::from("src/main.rs"),
+            ],
+            "Paths should be sorted by bytes, so - sorts before /"
+        );
+        assert_eq!(analysis.iter().next().unwrap().0, Path::new("src/main.rs"));
+    }
+
+    #[test]
+    fn test_get() {
+        let mut analysis = analysis(&[("src/main.rs", "Rust", 10), ("README.md", "Markdown", 5)]);
+        analysis.results.push(
+            "unknown".into(),
+            gix_hash::ObjectId::null(gix_hash::Kind::Sha1),
+            None,
+        );
+        let entry = analysis.get(Path::new("src").join("main.rs")).unwrap();
+        assert_eq!(entry.language().name(), "Rust");
+        assert!(analysis.get("src").is_none());
+        assert!(analysis.get("unknown").is_none());
+        assert!(analysis.get("missing.rs").is_none());
+
+        let other = self::analysis(&[("src/main.rs", "Rust", 20), ("lib.rs", "Rust", 30)]);
+        analysis.extend(other);
+        assert_eq!(analysis.get("src/main.rs").unwrap().size(), 10);
+        assert_eq!(analysis.get("lib.rs").unwrap().size(), 30);
+    }
+
+    #[test]
+    fn test_iter_under() {
+        let analysis = analysis(&[
+            ("services/api/server.rs", "Rust", 10),
+            ("services/apix/server.rs", "Rust", 20),
+            ("services/api/README.md", "Markdown", 5),
+        ]);
+        let paths: Vec<_> = analysis
+            .iter_under("services/api")
+            .map(|(path, _)| path.into_owned())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("services/api/server.rs"),
+                PathBuf::from("services/api/README.md")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_summary_under() {
+        let analysis = analysis(&[
+            ("services/api/server.rs", "Rust", 10),
+            ("services/apix/server.rs", "Rust", 20),
+            ("services/api/README.md", "Markdown", 5),
+        ]);
+        let summary = analysis.summary_under("services/api/", Default::default());
+        assert_eq!(summary.total(), 15);
+    }
+
+    #[test]
+    fn test_summary_by_category() {
+        let analysis = analysis(&[
+            ("main.rs", "Rust", 80),
+            ("index.html", "HTML", 15),
+            ("data.json", "JSON", 5),
+        ]);
+        let summaries = analysis.summary_by_category(Default::default());
+        assert_eq!(summaries.len(), 3);
+        assert_eq!(summaries[&Category::Programming].total(), 80);
+        assert_eq!(summaries[&Category::Markup].total(), 15);
+        assert_eq!(summaries[&Category::Data].total(), 5);
+        assert!(!summaries.contains_key(&Category::Prose));
+    }
+
+    #[test]
+    fn test_summary_by_top_level() {
+        let analysis = analysis(&[
+            ("services/api/server.rs", "Rust", 10),
+            ("libs/util.rs", "Rust", 20),
+            ("services/README.md", "Markdown", 5),
+            ("build.rs", "Rust", 1),
+        ]);
+        let summaries = analysis.summary_by_top_level(Default::default());
+        let totals: Vec<_> = summaries
+            .iter()
+            .map(|(directory, summary)| (directory.as_str(), summary.total()))
+            .collect();
+        assert_eq!(totals, vec![("services", 15), ("libs", 20), (".", 1)]);
+    }
+
+    #[test]
+    fn test_primary_language() {
+        let tied = analysis(&[
+            ("main.rs", "Rust", 10),
+            ("main.py", "Python", 10),
+            ("index.html", "HTML", 5),
+        ]);
+        assert_eq!(tied.primary_language().unwrap().name(), "Python");
+
+        let popular = analysis(&[("main.adb", "Ada", 10), ("main.rs", "Rust", 10)]);
+        assert_eq!(
+            popular.primary_language().unwrap().name(),
+            "Rust",
+            "Popular languages should win ties"
+        );
+
+        let mut largest = analysis(&[("main.rs", "Rust", 10), ("lib.c", "C", 5)]);
+        assert_eq!(largest.primary_language().unwrap().name(), "Rust");
+        for entry in largest.results.entries.iter_mut() {
+            entry.result.as_mut().unwrap().detectable = false;
+        }
+        assert!(largest.primary_language().is_none());
+    }
+
+    #[test]
+    fn test_summary_by_lines() {
+        let analysis = analysis(&[("main.rs", "Rust", 100), ("lib.rs", "Rust", 50)]);
+        let opts = SummaryOpts {
+            by_lines: true,
+            ..Default::default()
+        };
+        let summary = analysis.summary_with(opts);
+        assert_eq!(summary.total(), 15);
+    }
+
+    #[test]
+    fn test_summary_by_chars() {
+        let analysis = analysis(&[("main.rs", "Rust", 100), ("lib.rs", "Rust", 50)]);
+        let opts = SummaryOpts {
+            by_chars: true,
+            ..Default::default()
+        };
+        let summary = analysis.summary_with(opts);
+        assert_eq!(summary.total(), 75);
+    }
+
+    #[test]
+    fn test_summary_dedup_by_oid() {
+        let mut analysis = analysis(&[
+            ("vendor/a/lib.rs", "Rust", 100),
+            ("vendor/b/lib.rs", "Rust", 100),
+            ("main.rs", "Rust", 10),
+        ]);
+        analysis.results.entries[2].id = gix_hash::ObjectId::from([1; 20]);
+        assert_eq!(analysis.summary().total(), 210);
+        let opts = SummaryOpts {
+            dedup_by_oid: true,
+            ..Default::default()
+        };
+        assert_eq!(analysis.summary_with(opts).total(), 110);
+    }
+}
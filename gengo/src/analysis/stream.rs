@@ -0,0 +1,236 @@
+use super::SkipReason;
+use crate::{Entry, Gengo, PreparedSubmodule, Result, Results, Submodule};
+use gix::bstr::{BStr, BString, ByteSlice};
+use indexmap::IndexMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+
+/// Yields the entries of an analysis as each file is analyzed, instead of
+/// collecting them into an `Analysis`.
+///
+/// Files are analyzed one at a time, when the next entry is requested.
+/// Submodules are opened after the files of the repository that contains
+/// them. Files that weren't identified, or were skipped, aren't yielded,
+/// but the skipped files can be read with `skipped`. Submodules with the
+/// same tree are analyzed once, like in `analyze`.
+///
+/// If analyzing a file fails, the error is yielded, and the stream ends.
+///
+/// # Example
+///
+/// ```no_run
+/// use gengo::Builder;
+/// use std::collections::HashMap;
+///
+/// let gengo = Builder::new("path/to/repo").build().unwrap();
+/// let mut sizes = HashMap::new();
+/// for result in gengo.analyze_streaming("HEAD").unwrap() {
+///     let (_, entry) = result.unwrap();
+///     *sizes.entry(entry.language().name().to_string()).or_insert(0) += entry.size();
+/// }
+/// ```
+pub struct Stream<'gengo> {
+    /// The instance, which is owned if it was configured for the revision.
+    gengo: Cow<'gengo, Gengo>,
+    pending: VecDeque<Submodule>,
+    current: Option<Current>,
+    /// The results of a submodule with the same tree as one that was already
+    /// analyzed, with its root and the index of the next entry.
+    reused: Option<(BString, Results, usize)>,
+    /// The results of the submodules that were analyzed, by key.
+    analyzed: HashMap<(gix::ObjectId, BString), Results>,
+    /// The files that were skipped, and the submodules that were nested too
+    /// deeply.
+    skipped: Results,
+    submodule_commits: IndexMap<BString, gix::ObjectId>,
+    done: bool,
+}
+
+/// The repository or submodule whose files are being yielded.
+struct Current {
+    prepared: PreparedSubmodule,
+    /// The index of the next entry in the results.
+    next: usize,
+    /// The key of a submodule, whose results are kept so that they can be
+    /// reused. This is `None` for the root repository.
+    key: Option<(gix::ObjectId, BString)>,
+}
+
+impl<'gengo> Stream<'gengo> {
+    pub(crate) fn new(gengo: Cow<'gengo, Gengo>, root: Submodule) -> Self {
+        Self {
+            gengo,
+            pending: VecDeque::from([root]),
+            current: None,
+            reused: None,
+            analyzed: HashMap::new(),
+            skipped: Results::default(),
+            submodule_commits: IndexMap::new(),
+            done: false,
+        }
+    }
+
+    /// The files that were skipped so far, with the reason why, like
+    /// `Analysis::skipped`. Once the stream ends, this has every skipped
+    /// file.
+    pub fn skipped(&self) -> Vec<(BString, SkipReason)> {
+        let skipped = &self.skipped;
+        skipped
+            .entries
+            .iter()
+            .filter_map(|entry| Some((skipped.path(entry).to_owned(), entry.skipped?)))
+            .collect()
+    }
+
+    /// The commits that submodules were pinned to so far, like
+    /// `Analysis::submodule_commits`.
+    pub fn submodule_commits(&self) -> &IndexMap<BString, gix::ObjectId> {
+        &self.submodule_commits
+    }
+
+    /// Starts yielding the entries of a repository or submodule, and queues
+    /// the submodules nested in it. The results of a submodule with the same
+    /// tree as one that was already analyzed are reused.
+    fn start(&mut self, submodule: Submodule) -> Result<()> {
+        let key = submodule.key();
+        let submodules = if let Some(results) = self.analyzed.get(&key).cloned() {
+            let submodules = self.gengo.nested_submodules(&submodule)?;
+            self.reused = Some((submodule.root, results, 0));
+            submodules
+        } else {
+            let key = (!submodule.root.is_empty()).then_some(key);
+            let (prepared, submodules) = self.gengo.prepare_submodule(submodule)?;
+            self.current = Some(Current {
+                prepared,
+                next: 0,
+                key,
+            });
+            submodules
+        };
+        self.gengo.queue_submodules(
+            submodules,
+            &mut self.pending,
+            &mut self.skipped,
+            &mut self.submodule_commits,
+        );
+        Ok(())
+    }
+
+    /// Yields the next entry of a submodule that was already analyzed.
+    fn next_reused(&mut self) -> Option<(PathBuf, Entry)> {
+        let (root, results, next) = self.reused.as_mut()?;
+        while let Some(entry) = results.entries.get_mut(*next) {
+            *next += 1;
+            let path = results.path_storage[entry.path.clone()].as_bstr();
+            let full = join(root, path);
+            if let Some(reason) = entry.skipped {
+                skip(&mut self.skipped, full.as_bstr(), entry.id, reason);
+                continue;
+            }
+            let Some(result) = entry.result.take() else {
+                continue;
+            };
+            let Ok(full) = gix::path::try_from_bstr(full.as_bstr()) else {
+                continue;
+            };
+            return Some((full.into_owned(), result));
+        }
+        None
+    }
+
+    /// Analyzes the next file of the current repository or submodule.
+    /// Returns `None` when there are no more files in it.
+    fn next_in_current(&mut self) -> Option<Result<(PathBuf, Entry)>> {
+        let current = self.current.as_mut()?;
+        let PreparedSubmodule {
+            root,
+            repo,
+            state,
+            results,
+            vendored,
+        } = &mut current.prepared;
+        while let Some(entry) = results.entries.get_mut(current.next) {
+            current.next += 1;
+            let path = results.path_storage[entry.path.clone()].as_bstr();
+            let full = join(root, path);
+            if let Some(reason) = entry.skipped {
+                skip(&mut self.skipped, full.as_bstr(), entry.id, reason);
+                continue;
+            }
+            let Ok(relative) = gix::path::try_from_bstr(path) else {
+                continue;
+            };
+            if let Err(err) = self
+                .gengo
+                .analyze_blob(&relative, repo, state, entry, *vendored)
+            {
+                return Some(Err(err));
+            }
+            // NOTE The results of submodules are kept, so that they can be
+            //      reused by submodules with the same tree.
+            let result = if current.key.is_some() {
+                entry.result.clone()
+            } else {
+                entry.result.take()
+            };
+            let Some(result) = result else {
+                continue;
+            };
+            let Ok(full) = gix::path::try_from_bstr(full.as_bstr()) else {
+                continue;
+            };
+            return Some(Ok((full.into_owned(), result)));
+        }
+        None
+    }
+}
+
+/// Joins the root of a submodule and a path in it.
+fn join(root: &BString, path: &BStr) -> BString {
+    let mut full = root.clone();
+    full.extend_from_slice(path);
+    full
+}
+
+/// Records a skipped file.
+fn skip(skipped: &mut Results, path: &BStr, id: gix::ObjectId, reason: SkipReason) {
+    skipped.push(path, id, None);
+    if let Some(last) = skipped.entries.last_mut() {
+        last.skipped = Some(reason);
+    }
+}
+
+impl Iterator for Stream<'_> {
+    type Item = Result<(PathBuf, Entry)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            if let Some(item) = self.next_reused() {
+                return Some(Ok(item));
+            }
+            self.reused = None;
+            if let Some(item) = self.next_in_current() {
+                self.done = item.is_err();
+                return Some(item);
+            }
+            if let Some(Current {
+                prepared,
+                key: Some(key),
+                ..
+            }) = self.current.take()
+            {
+                self.analyzed.insert(key, prepared.results);
+            }
+            let Some(submodule) = self.pending.pop_front() else {
+                self.done = true;
+                break;
+            };
+            if let Err(err) = self.start(submodule) {
+                self.done = true;
+                return Some(Err(err));
+            }
+        }
+        None
+    }
+}
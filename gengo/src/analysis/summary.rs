@@ -1,9 +1,12 @@
 use crate::Language;
 use indexmap::map::Iter as IndexMapIter;
 use indexmap::IndexMap;
+use std::fmt;
 
 /// The summary of an analysis.
-#[derive(Debug)]
+///
+/// The default summary is empty, which is useful for merging many summaries.
+#[derive(Debug, Default)]
 pub struct Summary(pub(super) IndexMap<Language, usize>);
 
 impl Summary {
@@ -12,10 +15,91 @@ impl Summary {
         self.0.values().sum()
     }
 
+    /// Returns the size of a language, if it is in the summary.
+    pub fn get(&self, language: &Language) -> Option<usize> {
+        self.0.get(language).copied()
+    }
+
     /// Returns an iterator over the languages and their sizes.
     pub fn iter(&self) -> Iter<'_> {
         Iter(self.0.iter())
     }
+
+    /// Returns the languages and their sizes, sorted from largest to
+    /// smallest. Languages with the same size are sorted by name.
+    pub fn sorted_by_size(&self) -> Vec<(&Language, usize)> {
+        let mut sorted: Vec<_> = self
+            .iter()
+            .map(|(language, size)| (language, *size))
+            .collect();
+        sorted.sort_by(|(a_language, a_size), (b_language, b_size)| {
+            b_size
+                .cmp(a_size)
+                .then_with(|| a_language.name().cmp(b_language.name()))
+        });
+        sorted
+    }
+
+    /// Adds the sizes of another summary to this summary. Languages that are
+    /// only in `other` are added after the languages of this summary, in the
+    /// order of `other`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gengo::analysis::Summary;
+    /// use gengo::Builder;
+    ///
+    /// let mut total = Summary::default();
+    /// for path in ["path/to/first", "path/to/second"] {
+    ///     let gengo = Builder::new(path).build().unwrap();
+    ///     total.merge(&gengo.analyze("HEAD").unwrap().summary());
+    /// }
+    /// ```
+    pub fn merge(&mut self, other: &Summary) {
+        for (language, size) in other {
+            self.add(language, *size);
+        }
+    }
+
+    /// Adds to the size of a language, adding the language if it isn't in
+    /// the summary.
+    pub(crate) fn add(&mut self, language: &Language, size: usize) {
+        *self.0.entry(language.clone()).or_insert(0) += size;
+    }
+
+    /// Returns the percentage of the total size that each language makes up.
+    /// The languages are in the same order as the summary. When the total
+    /// is `0`, every language makes up `0` percent.
+    pub fn percentages(&self) -> IndexMap<Language, f64> {
+        let total = self.total();
+        self.iter()
+            .map(|(language, size)| (language.clone(), percent(*size, total)))
+            .collect()
+    }
+}
+
+/// Gets the percentage of `total` that `size` makes up, or `0` if `total` is
+/// `0`.
+fn percent(size: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        (size as f64) * 100.0 / (total as f64)
+    }
+}
+
+/// Formats one language per line, from largest to smallest, with its
+/// percentage of the total and its size.
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total();
+        for (language, size) in self.sorted_by_size() {
+            let percentage = percent(size, total);
+            writeln!(f, "{:>6.2}% {:<10} {}", percentage, size, language.name())?;
+        }
+        Ok(())
+    }
 }
 
 pub struct Iter<'map>(IndexMapIter<'map, Language, usize>);
@@ -37,10 +121,336 @@ impl<'map> IntoIterator for &'map Summary {
     }
 }
 
+/// Serializes as a map of language names to sizes.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Summary {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (language, size) in self.iter() {
+            map.serialize_entry(language.name(), size)?;
+        }
+        map.end()
+    }
+}
+
 /// Options to use when creating a summary.
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 #[non_exhaustive]
 pub struct Opts {
     /// Include all files, even if they are not detectable.
     pub all: bool,
+    /// Count the lines of each language instead of the bytes.
+    pub by_lines: bool,
+    /// Count the characters (Unicode scalar values) of each language instead
+    /// of the bytes, so that multibyte text isn't overweighted. Binary files
+    /// have no characters. `by_lines` takes precedence.
+    pub by_chars: bool,
+    /// Leave out languages that are smaller than this. When counting lines
+    /// or characters, this is a number of lines or characters.
+    pub min_bytes: usize,
+    /// Leave out languages that make up less than this percentage of the
+    /// total, from `0` to `100`.
+    pub min_percent: f64,
+    /// Keep only the largest languages that together make up this
+    /// percentage of the total, from `0` to `100`, and leave out the rest.
+    /// Languages are kept from largest to smallest until their combined
+    /// share reaches the threshold, so the largest language is always kept.
+    /// Languages with the same size as the last language that was kept are
+    /// also kept, so the result never depends on the order of ties.
+    pub keep_cumulative_percent: Option<f64>,
+    /// Count the files whose language wasn't identified, as "Unknown" for
+    /// text files, and "Binary" for binary files. Both are in the data
+    /// category. These are never included in `Analysis::summary_by_category`.
+    /// Unless `all` is set, files that are generated, documentation, or
+    /// vendored are left out, like the files whose language was identified.
+    pub include_unknown: bool,
+    /// Instead of leaving out languages that are below `min_bytes`,
+    /// `min_percent`, or `keep_cumulative_percent`, add their sizes to a
+    /// language named "Other". Like "Unknown" and "Binary", it is in the
+    /// data category, so summaries with "Other" can be merged.
+    pub other: bool,
+    /// Count the files with the same contents once, like copies of the same
+    /// vendored library. Only the first of the files is counted.
+    pub dedup_by_oid: bool,
+}
+
+impl Opts {
+    /// Leaves out, or collapses into "Other", the languages that are below
+    /// the thresholds.
+    pub(super) fn apply_thresholds(&self, summary: IndexMap<Language, usize>) -> Summary {
+        let total = summary.values().sum::<usize>();
+        let min_cumulative = self
+            .keep_cumulative_percent
+            .map_or(0, |threshold| cumulative_cutoff(&summary, total, threshold));
+        let mut kept = IndexMap::new();
+        let mut small = Vec::new();
+        for (language, size) in summary {
+            if size < self.min_bytes
+                || percent(size, total) < self.min_percent
+                || size < min_cumulative
+            {
+                small.push((language, size));
+            } else {
+                kept.insert(language, size);
+            }
+        }
+        let mut summary = Summary(kept);
+        if !self.other {
+            return summary;
+        }
+        if small.is_empty() {
+            return summary;
+        }
+        let size: usize = small.iter().map(|(_, size)| size).sum();
+        *summary.0.entry(Language::other()).or_insert(0) += size;
+        summary
+    }
+}
+
+/// Gets the size of the smallest language that is needed for the largest
+/// languages to make up `threshold` percent of the total.
+fn cumulative_cutoff(summary: &IndexMap<Language, usize>, total: usize, threshold: f64) -> usize {
+    let mut sizes: Vec<_> = summary.values().copied().collect();
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    let mut cumulative = 0;
+    for size in sizes {
+        cumulative += size;
+        if percent(cumulative, total) >= threshold {
+            return size;
+        }
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Analyzers;
+
+    fn summary(sizes: &[(&str, usize)]) -> Summary {
+        let analyzers = Analyzers::default();
+        Summary(
+            sizes
+                .iter()
+                .map(|(name, size)| (analyzers.get(name).unwrap().clone(), *size))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_total_and_get() {
+        let analyzers = Analyzers::default();
+        let summary = summary(&[("Rust", 75), ("Markdown", 25)]);
+        assert_eq!(summary.total(), 100);
+        assert_eq!(summary.get(analyzers.get("Rust").unwrap()), Some(75));
+        assert_eq!(summary.get(analyzers.get("JSON").unwrap()), None);
+    }
+
+    #[test]
+    fn test_percentages() {
+        let summary = summary(&[("Rust", 75), ("Markdown", 25)]);
+        let percentages: Vec<_> = summary
+            .percentages()
+            .into_iter()
+            .map(|(language, percentage)| (language.name().to_string(), percentage))
+            .collect();
+        assert_eq!(
+            percentages,
+            vec![("Rust".to_string(), 75.0), ("Markdown".to_string(), 25.0)]
+        );
+    }
+
+    #[test]
+    fn test_percentages_empty() {
+        let summary = summary(&[]);
+        assert!(summary.percentages().is_empty());
+    }
+
+    #[test]
+    fn test_percentages_zero_total() {
+        let summary = summary(&[("Rust", 0)]);
+        let percentages: Vec<_> = summary.percentages().into_values().collect();
+        assert_eq!(percentages, vec![0.0]);
+        assert_eq!(summary.to_string(), "  0.00% 0          Rust\n");
+    }
+
+    #[test]
+    fn test_sorted_by_size() {
+        let summary = summary(&[("Rust", 10), ("Markdown", 20), ("JSON", 10), ("C", 30)]);
+        let sorted: Vec<_> = summary
+            .sorted_by_size()
+            .into_iter()
+            .map(|(language, size)| (language.name(), size))
+            .collect();
+        assert_eq!(
+            sorted,
+            vec![("C", 30), ("Markdown", 20), ("JSON", 10), ("Rust", 10)]
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let summary = summary(&[("Markdown", 25), ("Rust", 75)]);
+        assert_eq!(
+            summary.to_string(),
+            " 75.00% 75         Rust\n 25.00% 25         Markdown\n"
+        );
+        assert_eq!(Summary::default().to_string(), "");
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let collapse = |opts: Opts| {
+            let summary = summary(&[("Rust", 900), ("Markdown", 60), ("JSON", 30), ("C", 10)]);
+            let summary = opts.apply_thresholds(summary.0);
+            summary
+                .iter()
+                .map(|(language, size)| (language.name().to_string(), *size))
+                .collect::<Vec<_>>()
+        };
+        let expected = |sizes: &[(&str, usize)]| {
+            sizes
+                .iter()
+                .map(|(name, size)| (name.to_string(), *size))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            collapse(Opts::default()),
+            expected(&[("Rust", 900), ("Markdown", 60), ("JSON", 30), ("C", 10)])
+        );
+        assert_eq!(
+            collapse(Opts {
+                min_bytes: 30,
+                ..Default::default()
+            }),
+            expected(&[("Rust", 900), ("Markdown", 60), ("JSON", 30)])
+        );
+        assert_eq!(
+            collapse(Opts {
+                min_percent: 5.0,
+                other: true,
+                ..Default::default()
+            }),
+            expected(&[("Rust", 900), ("Markdown", 60), ("Other", 40)])
+        );
+        assert_eq!(
+            collapse(Opts {
+                keep_cumulative_percent: Some(95.0),
+                ..Default::default()
+            }),
+            expected(&[("Rust", 900), ("Markdown", 60)])
+        );
+        assert_eq!(
+            collapse(Opts {
+                keep_cumulative_percent: Some(95.0),
+                other: true,
+                ..Default::default()
+            }),
+            expected(&[("Rust", 900), ("Markdown", 60), ("Other", 40)])
+        );
+        assert_eq!(
+            collapse(Opts {
+                keep_cumulative_percent: Some(0.0),
+                ..Default::default()
+            }),
+            expected(&[("Rust", 900)])
+        );
+        assert_eq!(
+            collapse(Opts {
+                keep_cumulative_percent: Some(100.0),
+                ..Default::default()
+            }),
+            expected(&[("Rust", 900), ("Markdown", 60), ("JSON", 30), ("C", 10)])
+        );
+    }
+
+    #[test]
+    fn test_keep_cumulative_percent_ties() {
+        let summary = summary(&[("Rust", 50), ("Markdown", 20), ("JSON", 20), ("C", 10)]);
+        let opts = Opts {
+            keep_cumulative_percent: Some(70.0),
+            ..Default::default()
+        };
+        let names: Vec<_> = opts
+            .apply_thresholds(summary.0)
+            .iter()
+            .map(|(language, _)| language.name().to_string())
+            .collect();
+        assert_eq!(names, vec!["Rust", "Markdown", "JSON"]);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut merged = Summary::default();
+        merged.merge(&summary(&[("Rust", 10), ("Markdown", 20)]));
+        merged.merge(&summary(&[("JSON", 5), ("Rust", 30)]));
+        let sizes: Vec<_> = merged
+            .iter()
+            .map(|(language, size)| (language.name(), *size))
+            .collect();
+        assert_eq!(sizes, vec![("Rust", 40), ("Markdown", 20), ("JSON", 5)]);
+    }
+
+    #[test]
+    fn test_merge_other() {
+        let names = |summary: &Summary| {
+            summary
+                .iter()
+                .map(|(language, size)| (language.name().to_string(), *size))
+                .collect::<Vec<_>>()
+        };
+        // NOTE The largest collapsed languages have different categories.
+        let first = summary(&[("Rust", 900), ("Markdown", 60), ("C", 40)]);
+        let second = summary(&[("Rust", 900), ("C", 60), ("Markdown", 40)]);
+        let opts = Opts {
+            min_percent: 5.0,
+            other: true,
+            ..Default::default()
+        };
+        let mut merged = opts.apply_thresholds(first.0);
+        merged.merge(&opts.apply_thresholds(second.0));
+        assert_eq!(
+            names(&merged),
+            vec![
+                ("Rust".to_string(), 1800),
+                ("Markdown".to_string(), 60),
+                ("Other".to_string(), 80),
+                ("C".to_string(), 60),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_cumulative_other() {
+        let first = summary(&[("Rust", 900), ("Markdown", 60), ("C", 40)]);
+        let second = summary(&[("Rust", 900), ("C", 60), ("Markdown", 40)]);
+        let opts = Opts {
+            keep_cumulative_percent: Some(90.0),
+            other: true,
+            ..Default::default()
+        };
+        let mut merged = opts.apply_thresholds(first.0);
+        merged.merge(&opts.apply_thresholds(second.0));
+        let others = merged
+            .iter()
+            .filter(|(language, _)| language.name() == "Other")
+            .count();
+        assert_eq!(others, 1);
+        assert_eq!(merged.get(&Language::other()), Some(200));
+        #[cfg(feature = "serde")]
+        assert_eq!(
+            serde_json::to_string(&merged).unwrap(),
+            r#"{"Rust":1800,"Other":200}"#
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serialize() {
+        let summary = summary(&[("Rust", 100), ("Markdown", 20)]);
+        let json = serde_json::to_string(&summary).unwrap();
+        assert_eq!(json, r#"{"Rust":100,"Markdown":20}"#);
+    }
 }
@@ -0,0 +1,263 @@
+//! Attribute overrides loaded from outside of a repository.
+#[cfg(feature = "git")]
+use gix::attrs::search::{MetadataCollection, Outcome};
+#[cfg(feature = "git")]
+use gix::attrs::{Search, StateRef};
+#[cfg(feature = "git")]
+use gix::glob::pattern::Case;
+#[cfg(feature = "git")]
+use std::io;
+#[cfg(feature = "git")]
+use std::path::Path;
+
+/// The number of attributes that can override the analysis of a file.
+#[cfg(feature = "git")]
+pub(crate) const ATTRIBUTE_COUNT: usize = 5;
+
+/// The attributes that can override the analysis of a file, followed by
+/// their Linguist equivalents, in the same order, and then by git's `text`
+/// attribute, which is unset for binary files.
+#[cfg(feature = "git")]
+pub(crate) const ATTRIBUTE_NAMES: [&str; ATTRIBUTE_COUNT * 2 + 1] = [
+    "gengo-language",
+    "gengo-generated",
+    "gengo-documentation",
+    "gengo-vendored",
+    "gengo-detectable",
+    "linguist-language",
+    "linguist-generated",
+    "linguist-documentation",
+    "linguist-vendored",
+    "linguist-detectable",
+    "text",
+];
+
+/// Interprets the state of a boolean attribute.
+///
+/// `attr` and `attr=true` are `Some(true)`, while `-attr` and `attr=false`
+/// are `Some(false)`. Unspecified attributes are `None`, so that the
+/// heuristics decide. Any other value is treated like `attr`.
+#[cfg(feature = "git")]
+pub(crate) fn flag(state: StateRef<'_>) -> Option<bool> {
+    match state {
+        StateRef::Unspecified => None,
+        StateRef::Unset => Some(false),
+        StateRef::Value(v) if v.as_bstr() == "false" => Some(false),
+        StateRef::Set | StateRef::Value(_) => Some(true),
+    }
+}
+
+/// How an attribute was assigned for a file.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Assignment {
+    /// The attribute wasn't specified, so the heuristics decided.
+    #[default]
+    Unspecified,
+    /// The attribute was set, like `attr`, `attr=true`, or, for
+    /// `gengo-language`, `attr=<language>`.
+    Set,
+    /// The attribute was unset, like `-attr` or `attr=false`.
+    Unset,
+}
+
+impl From<Option<bool>> for Assignment {
+    fn from(flag: Option<bool>) -> Self {
+        match flag {
+            None => Self::Unspecified,
+            Some(true) => Self::Set,
+            Some(false) => Self::Unset,
+        }
+    }
+}
+
+/// How each of the `gengo-*` attributes was assigned for a file. Linguist
+/// attributes are used when the `gengo-*` attributes aren't specified.
+///
+/// This is useful for reviewing which files are overridden by
+/// `.gitattributes`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AttributeState {
+    language: Assignment,
+    generated: Assignment,
+    documentation: Assignment,
+    vendored: Assignment,
+    detectable: Assignment,
+}
+
+impl AttributeState {
+    /// Creates the state from the flags of the attributes, in the order of
+    /// `ATTRIBUTE_NAMES`.
+    #[cfg(feature = "git")]
+    pub(crate) fn new(flags: [Option<bool>; ATTRIBUTE_COUNT]) -> Self {
+        let [language, generated, documentation, vendored, detectable] =
+            flags.map(Assignment::from);
+        Self {
+            language,
+            generated,
+            documentation,
+            vendored,
+            detectable,
+        }
+    }
+
+    /// The `gengo-language` attribute.
+    pub fn language(&self) -> Assignment {
+        self.language
+    }
+
+    /// The `gengo-generated` attribute.
+    pub fn generated(&self) -> Assignment {
+        self.generated
+    }
+
+    /// The `gengo-documentation` attribute.
+    pub fn documentation(&self) -> Assignment {
+        self.documentation
+    }
+
+    /// The `gengo-vendored` attribute.
+    pub fn vendored(&self) -> Assignment {
+        self.vendored
+    }
+
+    /// The `gengo-detectable` attribute.
+    pub fn detectable(&self) -> Assignment {
+        self.detectable
+    }
+}
+
+/// Overrides read from a `.gitattributes`-style file.
+#[cfg(feature = "git")]
+#[derive(Clone, Default)]
+pub(crate) struct Overrides {
+    search: Search,
+    collection: MetadataCollection,
+}
+
+#[cfg(feature = "git")]
+impl Overrides {
+    /// Reads overrides from a `.gitattributes`-style file. Patterns are matched
+    /// relative to the root of the analysis. If the file doesn't exist, there
+    /// are no overrides.
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut overrides = Self::default();
+        let mut buf = Vec::new();
+        overrides.search.add_patterns_file(
+            path.as_ref().to_owned(),
+            true,
+            None,
+            &mut buf,
+            &mut overrides.collection,
+            true,
+        )?;
+        Ok(overrides)
+    }
+
+    /// Creates an outcome that holds matches of the overriding attributes.
+    pub fn outcome(&self) -> Outcome {
+        let mut outcome = Outcome::default();
+        outcome.initialize_with_selection(&self.collection, ATTRIBUTE_NAMES);
+        outcome
+    }
+
+    /// Matches the attributes for a path, writing them to `out`.
+    pub fn matching_attributes(&self, filepath: &Path, out: &mut Outcome) {
+        out.reset();
+        let filepath = gix::path::into_bstr(filepath);
+        self.search.pattern_matching_relative_path(
+            filepath.as_ref(),
+            Case::Sensitive,
+            Some(false),
+            out,
+        );
+    }
+}
+
+#[cfg(all(test, feature = "git"))]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::io::Write;
+
+    #[rstest(
+        state,
+        expected,
+        case(StateRef::Unspecified, None),
+        case(StateRef::Set, Some(true)),
+        case(StateRef::Unset, Some(false)),
+        case(StateRef::Value("true".into()), Some(true)),
+        case(StateRef::Value("false".into()), Some(false)),
+        case(StateRef::Value("yes".into()), Some(true))
+    )]
+    fn test_flag(state: StateRef<'_>, expected: Option<bool>) {
+        assert_eq!(flag(state), expected);
+    }
+
+    #[test]
+    fn test_attribute_state() {
+        let state = AttributeState::new([Some(true), None, Some(false), None, Some(true)]);
+        assert_eq!(state.language(), Assignment::Set);
+        assert_eq!(state.generated(), Assignment::Unspecified);
+        assert_eq!(state.documentation(), Assignment::Unset);
+        assert_eq!(state.vendored(), Assignment::Unspecified);
+        assert_eq!(state.detectable(), Assignment::Set);
+        assert_eq!(
+            AttributeState::new([None; ATTRIBUTE_COUNT]),
+            AttributeState::default()
+        );
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let overrides = Overrides::from_file("does/not/exist/.gitattributes").unwrap();
+        let mut outcome = overrides.outcome();
+        overrides.matching_attributes(Path::new("src/main.rs"), &mut outcome);
+        assert!(outcome
+            .iter_selected()
+            .all(|m| m.assignment.state == StateRef::Unspecified));
+    }
+
+    #[test]
+    fn test_matching_attributes() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "*.rs gengo-language=Plain-Text -gengo-detectable linguist-vendored"
+        )
+        .unwrap();
+        writeln!(file, "dist/** gengo-generated").unwrap();
+        let overrides = Overrides::from_file(file.path()).unwrap();
+        let mut outcome = overrides.outcome();
+
+        overrides.matching_attributes(Path::new("src/main.rs"), &mut outcome);
+        let states: Vec<_> = outcome
+            .iter_selected()
+            .map(|m| m.assignment.state)
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                StateRef::Value("Plain-Text".into()),
+                StateRef::Unspecified,
+                StateRef::Unspecified,
+                StateRef::Unspecified,
+                StateRef::Unset,
+                StateRef::Unspecified,
+                StateRef::Unspecified,
+                StateRef::Unspecified,
+                StateRef::Set,
+                StateRef::Unspecified,
+                StateRef::Unspecified,
+            ]
+        );
+
+        overrides.matching_attributes(Path::new("dist/index.js"), &mut outcome);
+        let states: Vec<_> = outcome
+            .iter_selected()
+            .map(|m| m.assignment.state)
+            .collect();
+        assert_eq!(states[1], StateRef::Set);
+    }
+}
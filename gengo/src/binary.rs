@@ -0,0 +1,34 @@
+//! Detects binary contents.
+
+/// Byte order marks for UTF-16 text, which can contain NUL bytes.
+const UTF16_BOMS: [&[u8]; 2] = [b"\xFF\xFE", b"\xFE\xFF"];
+
+/// Checks if the contents are binary by scanning them for a NUL byte.
+///
+/// Contents starting with a UTF-16 byte order mark are text.
+pub(crate) fn is_binary(contents: &[u8]) -> bool {
+    if UTF16_BOMS.iter().any(|bom| contents.starts_with(bom)) {
+        return false;
+    }
+    contents.contains(&0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        contents,
+        expected,
+        case(b"fn main() {}\n", false),
+        case(b"", false),
+        case(b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR", true),
+        case(b"\xFF\xFEf\x00n\x00 \x00m\x00a\x00i\x00n\x00", false),
+        case(b"\xFE\xFF\x00f\x00n\x00 \x00m\x00a\x00i\x00n", false),
+        case(b"f\x00n\x00 \x00m\x00a\x00i\x00n\x00", true)
+    )]
+    fn test_is_binary(contents: &[u8], expected: bool) {
+        assert_eq!(is_binary(contents), expected);
+    }
+}
@@ -0,0 +1,215 @@
+//! Attributes the lines of files to the authors of the commits that last
+//! changed them.
+use super::{Language, Result};
+use crate::analysis::Summary;
+use gix::bstr::ByteSlice;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The largest number of line pairs that are compared when diffing the
+/// changed part of a file. Larger changes are treated as replacing every
+/// changed line.
+const MAX_DIFF_CELLS: usize = 1 << 22;
+
+/// The author of a commit.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Author {
+    name: String,
+    email: String,
+}
+
+impl Author {
+    /// The name of the author.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The email of the author.
+    pub fn email(&self) -> &str {
+        &self.email
+    }
+}
+
+/// A file whose lines haven't all been attributed yet.
+struct Pending {
+    path: PathBuf,
+    language: Language,
+    /// The version of the file in the commit being visited.
+    blob: gix::ObjectId,
+    /// The lines that aren't attributed yet, as indices into the lines of `blob`.
+    lines: Vec<usize>,
+}
+
+/// Blames the files at `commit_id`, and counts the attributed lines of each
+/// author by language. Only the first parent of each commit is followed, and
+/// renamed files are attributed to the commit that renamed them.
+pub(crate) fn blame(
+    repo: &gix::Repository,
+    commit_id: gix::ObjectId,
+    files: Vec<(PathBuf, Language)>,
+    interrupt: &AtomicBool,
+) -> Result<HashMap<Author, Summary>> {
+    let mut buf = Vec::new();
+    let commit = repo.find_object(commit_id)?.try_into_commit()?;
+    let tree = commit.tree()?;
+    let mut pending = Vec::new();
+    for (path, language) in files {
+        let Some(entry) = tree.lookup_entry_by_path(&path, &mut buf)? else {
+            continue;
+        };
+        if !entry.mode().is_blob() {
+            continue;
+        }
+        let blob = entry.object_id();
+        let count = lines(&repo.find_object(blob)?.data).len();
+        pending.push(Pending {
+            path,
+            language,
+            blob,
+            lines: (0..count).collect(),
+        });
+    }
+
+    let mut authors: HashMap<Author, Summary> = HashMap::new();
+    let mut commit = commit;
+    while !pending.is_empty() && !interrupt.load(Ordering::Relaxed) {
+        let signature = commit.author()?;
+        let author = Author {
+            name: signature.name.to_str_lossy().into_owned(),
+            email: signature.email.to_str_lossy().into_owned(),
+        };
+        let parent = commit
+            .parent_ids()
+            .next()
+            .map(|id| id.object())
+            .transpose()?
+            .map(|object| object.try_into_commit())
+            .transpose()?;
+        let parent_tree = parent.as_ref().map(|parent| parent.tree()).transpose()?;
+
+        let mut still_pending = Vec::with_capacity(pending.len());
+        for mut file in pending {
+            let previous = match &parent_tree {
+                Some(tree) => tree
+                    .lookup_entry_by_path(&file.path, &mut buf)?
+                    .filter(|entry| entry.mode().is_blob())
+                    .map(|entry| entry.object_id()),
+                None => None,
+            };
+            let attributed = match previous {
+                Some(previous) if previous == file.blob => 0,
+                Some(previous) => {
+                    let old = repo.find_object(previous)?;
+                    let new = repo.find_object(file.blob)?;
+                    let mapping = diff(&lines(&old.data), &lines(&new.data));
+                    let before = file.lines.len();
+                    file.lines = file.lines.iter().filter_map(|&i| mapping[i]).collect();
+                    file.blob = previous;
+                    before - file.lines.len()
+                }
+                None => std::mem::take(&mut file.lines).len(),
+            };
+            if attributed > 0 {
+                authors
+                    .entry(author.clone())
+                    .or_default()
+                    .add(&file.language, attributed);
+            }
+            if !file.lines.is_empty() {
+                still_pending.push(file);
+            }
+        }
+        pending = still_pending;
+        let Some(parent) = parent else {
+            break;
+        };
+        commit = parent;
+    }
+    Ok(authors)
+}
+
+/// Splits contents into lines, without their line endings.
+fn lines(contents: &[u8]) -> Vec<&[u8]> {
+    contents.lines().collect()
+}
+
+/// Maps each line of `new` to the same line in `old`, or `None` if the line
+/// was added.
+fn diff(old: &[&[u8]], new: &[&[u8]]) -> Vec<Option<usize>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut mapping: Vec<Option<usize>> = (0..prefix).map(Some).collect();
+    mapping.extend(
+        lcs(old_middle, new_middle)
+            .into_iter()
+            .map(|i| i.map(|i| i + prefix)),
+    );
+    mapping.extend((old.len() - suffix..old.len()).map(Some));
+    mapping
+}
+
+/// Maps each line of `new` to a line of `old` along their longest common
+/// subsequence.
+fn lcs(old: &[&[u8]], new: &[&[u8]]) -> Vec<Option<usize>> {
+    let mut mapping = vec![None; new.len()];
+    if old.is_empty() || new.is_empty() || old.len() * new.len() > MAX_DIFF_CELLS {
+        return mapping;
+    }
+    // NOTE lengths[i][j] is the length of the longest common subsequence of
+    //      old[i..] and new[j..].
+    let width = new.len() + 1;
+    let mut lengths = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            mapping[j] = Some(i);
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    mapping
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let old: Vec<&[u8]> = vec![b"a", b"b", b"c", b"d"];
+        let new: Vec<&[u8]> = vec![b"a", b"x", b"c", b"d", b"e"];
+        assert_eq!(
+            diff(&old, &new),
+            vec![Some(0), None, Some(2), Some(3), None]
+        );
+    }
+
+    #[test]
+    fn test_diff_moved_lines() {
+        let old: Vec<&[u8]> = vec![b"a", b"b", b"c"];
+        let new: Vec<&[u8]> = vec![b"c", b"a", b"b"];
+        assert_eq!(diff(&old, &new), vec![None, Some(0), Some(1)]);
+    }
+}
@@ -1,12 +1,24 @@
+use super::config::Config;
 use super::documentation::Documentation;
 use super::generated::Generated;
+use super::languages::Category;
 use super::vendored::Vendored;
 use super::Analyzers;
+use super::BlobCache;
+use super::BlobReader;
 use super::Gengo;
+use super::Language;
+use super::LanguageSet;
+#[cfg(feature = "git")]
+use super::Overrides;
+use super::SubmodulePolicy;
+use super::{Detector, FullDetector};
 use super::{Error, ErrorKind};
-use gix::discover::Error as DiscoverError;
-use std::error::Error as ErrorTrait;
-use std::path::Path;
+use glob::Pattern;
+use regex::RegexSet;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Builds a new `Gengo` instance.
 ///
@@ -16,20 +28,69 @@ use std::path::Path;
 /// use gengo::Builder;
 /// let gengo = Builder::new("path/to/repo").build().unwrap();
 /// ```
+// NOTE Without the git feature, the settings for repositories are unused.
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
 pub struct Builder<P: AsRef<Path>> {
     repository_path: P,
     analyzers: Option<Analyzers>,
+    detector: Option<Box<dyn Detector>>,
+    blob_reader: Option<Box<dyn BlobReader>>,
+    languages: Option<LanguageSet>,
     read_limit: Option<usize>,
+    max_blob_size: Option<usize>,
+    threads: Option<usize>,
+    attributes_file: Option<PathBuf>,
+    generated_globs: Vec<String>,
+    generated_signatures: Vec<String>,
+    documentation_globs: Vec<String>,
+    vendored_globs: Vec<String>,
+    vendored_patterns: Vec<String>,
+    exclude_globs: Vec<String>,
+    only_extensions: Option<HashSet<String>>,
+    cache: Option<Arc<BlobCache>>,
+    submodules: Option<SubmodulePolicy>,
+    max_submodule_depth: Option<usize>,
+    detectable_categories: Option<HashSet<Category>>,
+    category_overrides: HashMap<Language, Category>,
+    repository_config: bool,
+    untracked: bool,
+    embedded_languages: bool,
 }
 
 impl<P: AsRef<Path>> Builder<P> {
+    /// The default limit for how many bytes are read from each file for
+    /// heuristic analysis: 1 MiB.
     pub const DEFAULT_READ_LIMIT: usize = 1 << 20;
 
+    /// The default limit for how deeply submodules are recursed into.
+    pub const DEFAULT_MAX_SUBMODULE_DEPTH: usize = 10;
+
     pub fn new(repository_path: P) -> Self {
         Self {
             repository_path,
             analyzers: None,
+            detector: None,
+            blob_reader: None,
+            languages: None,
             read_limit: None,
+            max_blob_size: None,
+            threads: None,
+            attributes_file: None,
+            generated_globs: Vec::new(),
+            generated_signatures: Vec::new(),
+            documentation_globs: Vec::new(),
+            vendored_globs: Vec::new(),
+            vendored_patterns: Vec::new(),
+            exclude_globs: Vec::new(),
+            only_extensions: None,
+            cache: None,
+            submodules: None,
+            max_submodule_depth: None,
+            detectable_categories: None,
+            category_overrides: HashMap::new(),
+            repository_config: false,
+            untracked: false,
+            embedded_languages: false,
         }
     }
 
@@ -40,6 +101,30 @@ impl<P: AsRef<Path>> Builder<P> {
         self
     }
 
+    /// Sets the strategy for identifying the language of each file. If this
+    /// is not set, `FullDetector` will be used.
+    pub fn detector(mut self, detector: Box<dyn Detector>) -> Self {
+        self.detector = Some(detector);
+        self
+    }
+
+    /// Sets a hook that can replace the contents of blobs before they are
+    /// analyzed, like to fetch the files that Git LFS pointers point to.
+    /// Pointers that aren't replaced are identified by their path, and have
+    /// the size of the file they point to.
+    pub fn blob_reader(mut self, reader: Box<dyn BlobReader>) -> Self {
+        self.blob_reader = Some(reader);
+        self
+    }
+
+    /// Sets additional languages to merge into the `Analyzers`. These take
+    /// precedence over the other languages on extension and filename
+    /// collisions.
+    pub fn languages(mut self, languages: LanguageSet) -> Self {
+        self.languages = Some(languages);
+        self
+    }
+
     /// Sets the limit for how many bytes should be read from each file for
     /// heuristic analysis. If this is not set, `DEFAULT_READ_LIMIT` will be
     /// used.
@@ -48,27 +133,415 @@ impl<P: AsRef<Path>> Builder<P> {
         self
     }
 
-    pub fn build(self) -> Result<Gengo, Box<dyn ErrorTrait>> {
-        let repository = match gix::discover(self.repository_path) {
-            Ok(r) => r,
-            Err(DiscoverError::Discover(err)) => {
-                return Err(Box::new(Error::with_source(ErrorKind::NoRepository, err)))
-            }
-            Err(err) => return Err(err.into()),
+    /// Sets the size, in bytes, above which blobs aren't loaded into memory,
+    /// to limit the memory used for huge files like checked-in data. Files
+    /// in the worktree and loose objects that are larger, including loose
+    /// objects in alternates like those of `git clone --shared`, only have
+    /// their first `read_limit` bytes read for heuristics. Packed objects
+    /// that are larger, in the repository or its alternates, are identified
+    /// by their path, because they can't be partially read. Their size is still their full size, but their lines and
+    /// characters aren't counted. If this is not set, every blob is loaded.
+    pub fn max_blob_size(mut self, max_blob_size: usize) -> Self {
+        self.max_blob_size = Some(max_blob_size);
+        self
+    }
+
+    /// Sets the maximum number of threads used to analyze files, and the
+    /// maximum number of submodules to analyze concurrently. Submodules that
+    /// are analyzed concurrently split the threads between them. If this is
+    /// not set, or is `0`, the available parallelism is used. `1` analyzes
+    /// each file sequentially, which is useful for debugging.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Sets a `.gitattributes`-style file to read `gengo-*` overrides from.
+    /// Its patterns are matched relative to the root of the repository, and its
+    /// overrides take precedence over the repository's attributes. If the file
+    /// doesn't exist, it is ignored.
+    pub fn attributes_file<A: Into<PathBuf>>(mut self, attributes_file: A) -> Self {
+        self.attributes_file = Some(attributes_file.into());
+        self
+    }
+
+    /// Adds globs for paths that should be generated. The globs are matched
+    /// relative to the root of the repository, and support `**`. These are
+    /// overridden by the `gengo-generated` attribute.
+    pub fn generated_globs<I, S>(mut self, globs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.generated_globs
+            .extend(globs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds signatures for files that should be generated, for generators
+    /// that the built-in banners, like Go's `// Code generated ... DO NOT
+    /// EDIT.` and `@generated`, don't cover. A file is generated if any of
+    /// its first 10 lines contain a signature. These are overridden by the
+    /// `gengo-generated` attribute.
+    pub fn generated_signatures<I, S>(mut self, signatures: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.generated_signatures
+            .extend(signatures.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds globs for paths that should be documentation. The globs are
+    /// matched relative to the root of the repository, and support `**`.
+    /// These are overridden by the `gengo-documentation` attribute.
+    pub fn documentation_globs<I, S>(mut self, globs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.documentation_globs
+            .extend(globs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds globs for paths that should be vendored. The globs are matched
+    /// relative to the root of the repository, and support `**`. These are
+    /// overridden by the `gengo-vendored` attribute.
+    pub fn vendored_globs<I, S>(mut self, globs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.vendored_globs
+            .extend(globs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds regular expressions for paths that should be vendored, in
+    /// addition to the built-in ones, which are the same as Linguist's. The
+    /// expressions are matched against the whole path relative to the root
+    /// of the repository, so `^` anchors them to the root and `(^|/)` to any
+    /// directory. These are overridden by the `gengo-vendored` attribute.
+    pub fn vendored_patterns<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.vendored_patterns
+            .extend(patterns.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds globs for paths that should not be analyzed at all, like
+    /// `node_modules/**`. The globs are matched relative to the root of the
+    /// repository, and support `**`. Unlike vendored files, excluded files
+    /// aren't read, and aren't included in the analysis.
+    pub fn exclude_globs<I, S>(mut self, globs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exclude_globs.extend(globs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Only analyzes files with one of these extensions, without the leading
+    /// `.`, like `["rs", "toml"]`. Like excluded files, other files aren't
+    /// read, and aren't included in the analysis. Extensions are compared
+    /// case-insensitively. If this is not set, every file is analyzed.
+    pub fn only_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.only_extensions = Some(
+            extensions
+                .into_iter()
+                .map(|extension| extension.as_ref().to_lowercase())
+                .collect(),
+        );
+        self
+    }
+
+    /// Sets if `Gengo::analyze_worktree` also analyzes untracked files that
+    /// aren't ignored by `.gitignore` files or `.git/info/exclude`. Their
+    /// entries are marked with `Entry::is_untracked`. Defaults to `false`.
+    pub fn untracked(mut self, enabled: bool) -> Self {
+        self.untracked = enabled;
+        self
+    }
+
+    /// Sets if the languages embedded in Vue, HTML, and Markdown files are
+    /// found, like the languages of `<script>` tags and fenced code blocks.
+    /// This reads the contents of these files again, so it is disabled by
+    /// default. See `Entry::embedded_languages`.
+    pub fn embedded_languages(mut self, enabled: bool) -> Self {
+        self.embedded_languages = enabled;
+        self
+    }
+
+    /// Sets a cache for the results of analyzing blobs. The cache can be
+    /// shared with other `Gengo` instances, so unchanged blobs are not read
+    /// again when analyzing successive revisions.
+    pub fn cache(mut self, cache: Arc<BlobCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Sets how submodules are analyzed. If this is not set,
+    /// `SubmodulePolicy::RecurseAsVendored` will be used.
+    pub fn submodules(mut self, policy: SubmodulePolicy) -> Self {
+        self.submodules = Some(policy);
+        self
+    }
+
+    /// Sets how deeply nested submodules are recursed into. Submodules of the
+    /// repository have a depth of `1`, and `0` doesn't analyze any
+    /// submodules. Submodules past the limit are skipped. If this is not set,
+    /// `DEFAULT_MAX_SUBMODULE_DEPTH` will be used.
+    pub fn max_submodule_depth(mut self, depth: usize) -> Self {
+        self.max_submodule_depth = Some(depth);
+        self
+    }
+
+    /// Sets the categories of languages whose files are detectable, for
+    /// example to count `Prose` in a documentation repository. Files that are
+    /// binary, generated, documentation, or vendored are still not
+    /// detectable, and the `gengo-detectable` attribute takes precedence. If
+    /// this is not set, the categories where `Category::is_detectable` is
+    /// `true` will be used.
+    pub fn detectable_categories<I>(mut self, categories: I) -> Self
+    where
+        I: IntoIterator<Item = Category>,
+    {
+        self.detectable_categories = Some(categories.into_iter().collect());
+        self
+    }
+
+    /// Overrides the categories of languages when deciding if their files
+    /// are detectable, for example to count `SQL` as `Programming`. The
+    /// languages keep their categories otherwise, like in
+    /// `Analysis::summary_by_category`.
+    pub fn category_overrides<I>(mut self, overrides: I) -> Self
+    where
+        I: IntoIterator<Item = (Language, Category)>,
+    {
+        self.category_overrides.extend(overrides);
+        self
+    }
+
+    /// Sets whether settings are read from a `.gengo.toml` file at the root
+    /// of the repository. The file is read from the tree of each revision
+    /// that is analyzed, so older revisions are analyzed with their own
+    /// settings, and from the worktree by `Gengo::analyze_worktree`. It is
+    /// ignored if it doesn't exist, and is an error with
+    /// `ErrorKind::InvalidConfig` when the revision is analyzed if it is
+    /// invalid. It can set
+    /// the read limit, add languages, add globs for generated,
+    /// documentation, vendored, and excluded paths, and add regular
+    /// expressions for vendored paths:
+    ///
+    /// ```toml
+    /// read_limit = 65536
+    /// generated = ["dist/**"]
+    /// vendored = ["third_party/**"]
+    /// vendored_patterns = ['\.pb\.go$']
+    ///
+    /// [languages."My DSL"]
+    /// category = "programming"
+    /// color = "#123456"
+    /// extensions = ["mydsl"]
+    /// filenames = ["Dslfile"]
+    /// ```
+    ///
+    /// The globs are added to the ones set on the builder, and, like them,
+    /// are overridden by `.gitattributes`. Other settings on the builder take
+    /// precedence over the file. This is `false` by default.
+    pub fn repository_config(mut self, enabled: bool) -> Self {
+        self.repository_config = enabled;
+        self
+    }
+
+    /// Opens the repository and builds the `Gengo` instance.
+    ///
+    /// Returns an error with `ErrorKind::NoRepository` if no repository
+    /// contains the path, and an error with another kind if the repository
+    /// can't be opened or a setting is invalid.
+    #[cfg(feature = "git")]
+    pub fn build(self) -> Result<Gengo, Error> {
+        let repository = crate::discover(&self.repository_path)?;
+        let repository = gix::open(repository.path())
+            .map_err(|err| Error::with_source(ErrorKind::Discovery, err))?;
+        self.build_with(Some(repository))
+    }
+
+    /// Builds a `Gengo` instance without opening a repository, so that it
+    /// can run where there is no filesystem, like in a web browser. The
+    /// repository path and `repository_config` are ignored.
+    ///
+    /// The instance can only analyze other sources with
+    /// `Gengo::analyze_source`, and analyzing a revision returns an error
+    /// with `ErrorKind::NoRepository`.
+    pub fn build_without_repository(self) -> Result<Gengo, Error> {
+        #[cfg(feature = "git")]
+        return self.build_with(None);
+        #[cfg(not(feature = "git"))]
+        return self.build_with();
+    }
+
+    fn build_with(
+        self,
+        #[cfg(feature = "git")] repository: Option<gix::Repository>,
+    ) -> Result<Gengo, Error> {
+        let settings = Settings {
+            analyzers: self.analyzers,
+            languages: self.languages,
+            read_limit: self.read_limit,
+            generated_globs: self.generated_globs,
+            generated_signatures: self.generated_signatures,
+            documentation_globs: self.documentation_globs,
+            vendored_globs: self.vendored_globs,
+            vendored_patterns: self.vendored_patterns,
+            exclude_globs: self.exclude_globs,
+        };
+        // NOTE The settings are kept so that they can be combined with the
+        //      configuration of each revision.
+        #[cfg(feature = "git")]
+        let repository_config = self.repository_config && repository.is_some();
+        #[cfg(not(feature = "git"))]
+        let repository_config = false;
+        let (configured, settings) = if repository_config {
+            (
+                settings.clone().configure(Config::default())?,
+                Some(Arc::new(settings)),
+            )
+        } else {
+            (settings.configure(Config::default())?, None)
+        };
+        let detector = self.detector.unwrap_or_else(|| Box::new(FullDetector));
+        let threads = match self.threads {
+            None | Some(0) => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            Some(threads) => threads,
+        };
+        #[cfg(feature = "git")]
+        let overrides = match self.attributes_file {
+            Some(path) => Overrides::from_file(&path).map_err(|err| {
+                Error::with_source(ErrorKind::AttributesFile, err)
+                    .with_context(path.display().to_string())
+            })?,
+            None => Overrides::default(),
         };
-        let repository = gix::open(repository.path())?;
-        let analyzers = self.analyzers.unwrap_or_default();
-        let read_limit = self.read_limit.unwrap_or(Self::DEFAULT_READ_LIMIT);
-        let documentation = Documentation::new();
-        let generated = Generated::new();
-        let vendored = Vendored::new();
         Ok(Gengo {
-            repository: repository.into_sync(),
+            #[cfg(feature = "git")]
+            repository: repository.map(gix::Repository::into_sync),
+            analyzers: configured.analyzers,
+            detector: detector.into(),
+            read_limit: configured.read_limit,
+            threads,
+            documentation: configured.documentation,
+            generated: configured.generated,
+            vendored: configured.vendored,
+            #[cfg(feature = "git")]
+            overrides,
+            cache: self.cache,
+            submodules: self.submodules.unwrap_or_default(),
+            max_submodule_depth: self
+                .max_submodule_depth
+                .unwrap_or(Self::DEFAULT_MAX_SUBMODULE_DEPTH),
+            exclude: configured.exclude,
+            only_extensions: self.only_extensions,
+            blob_reader: self.blob_reader.map(Into::into),
+            max_blob_size: self.max_blob_size,
+            category_overrides: self.category_overrides,
+            untracked: self.untracked,
+            embedded_languages: self.embedded_languages,
+            settings,
+            config_id: None,
+            detectable_categories: self
+                .detectable_categories
+                .unwrap_or_else(|| Category::all().filter(Category::is_detectable).collect()),
+        })
+    }
+}
+
+/// The settings of a builder that a `.gengo.toml` file adds to, so that each
+/// revision can be analyzed with its own configuration.
+#[derive(Clone)]
+pub(crate) struct Settings {
+    analyzers: Option<Analyzers>,
+    languages: Option<LanguageSet>,
+    read_limit: Option<usize>,
+    generated_globs: Vec<String>,
+    generated_signatures: Vec<String>,
+    documentation_globs: Vec<String>,
+    vendored_globs: Vec<String>,
+    vendored_patterns: Vec<String>,
+    exclude_globs: Vec<String>,
+}
+
+/// The parts of a `Gengo` instance that depend on the configuration.
+pub(crate) struct Configured {
+    pub(crate) analyzers: Analyzers,
+    pub(crate) read_limit: usize,
+    pub(crate) documentation: Documentation,
+    pub(crate) generated: Generated,
+    pub(crate) vendored: Vendored,
+    pub(crate) exclude: Vec<Pattern>,
+}
+
+impl Settings {
+    /// Combines the settings with a configuration. The globs are added to
+    /// the ones of the settings, and the settings take precedence otherwise.
+    pub(crate) fn configure(mut self, config: Config) -> Result<Configured, Error> {
+        let languages = if config.has_languages() {
+            Some(config.languages(self.languages.unwrap_or_default()))
+        } else {
+            self.languages
+        };
+        self.generated_globs.extend(config.generated);
+        self.documentation_globs.extend(config.documentation);
+        self.vendored_globs.extend(config.vendored);
+        self.vendored_patterns.extend(config.vendored_patterns);
+        self.exclude_globs.extend(config.exclude);
+        let analyzers = self.analyzers.unwrap_or_default();
+        let analyzers = match languages {
+            Some(languages) => languages.merge(analyzers).map_err(|err| {
+                // NOTE The error isn't thread-safe, so only its message is kept.
+                Error::new(ErrorKind::InvalidLanguages).with_context(err.to_string())
+            })?,
+            None => analyzers,
+        };
+        let read_limit = self
+            .read_limit
+            .or(config.read_limit)
+            .unwrap_or(Builder::<&Path>::DEFAULT_READ_LIMIT);
+        let documentation = Documentation::new(patterns(&self.documentation_globs)?);
+        let generated = Generated::new(patterns(&self.generated_globs)?, self.generated_signatures);
+        let vendored = Vendored::new(
+            patterns(&self.vendored_globs)?,
+            RegexSet::new(&self.vendored_patterns)
+                .map_err(|err| Error::with_source(ErrorKind::InvalidRegex, err))?,
+        );
+        let exclude = patterns(&self.exclude_globs)?;
+        Ok(Configured {
             analyzers,
             read_limit,
             documentation,
             generated,
             vendored,
+            exclude,
         })
     }
 }
+
+fn patterns(globs: &[String]) -> Result<Vec<Pattern>, Error> {
+    globs
+        .iter()
+        .map(|g| {
+            Pattern::new(g)
+                .map_err(|err| Error::with_source(ErrorKind::InvalidGlob, err).with_context(g))
+        })
+        .collect()
+}
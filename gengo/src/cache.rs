@@ -0,0 +1,263 @@
+use super::{Detection, Language};
+use gix_hash::ObjectId;
+use lru::LruCache;
+use std::num::NonZeroUsize;
+#[cfg(feature = "git")]
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Caches the results of analyzing blobs, so that unchanged blobs don't need
+/// to be read again when analyzing successive revisions.
+///
+/// Results are keyed by the blob's object ID, and are only reused for the
+/// same filepath, executable bit, and `.gengo.toml` file. Attributes are not
+/// cached, so changes to `.gitattributes` are still respected. A cache should
+/// only be shared between `Gengo` instances with the same configuration, but
+/// they can analyze different repositories, like repositories that share
+/// submodules.
+///
+/// A cache created with `BlobCache::with_capacity` evicts the least recently
+/// used blob when it is full, so it can be kept for as long as a server runs.
+///
+/// # Example
+///
+/// ```no_run
+/// use gengo::{BlobCache, Builder};
+/// use std::sync::Arc;
+///
+/// let cache = Arc::new(BlobCache::new());
+/// let gengo = Builder::new("path/to/repo").cache(Arc::clone(&cache)).build().unwrap();
+/// gengo.analyze("HEAD~1").unwrap();
+/// gengo.analyze("HEAD").unwrap();
+/// cache.clear();
+/// ```
+#[derive(Debug)]
+pub struct BlobCache {
+    /// The cached blobs, which are `None` if the capacity is `0`.
+    blobs: Option<Mutex<LruCache<ObjectId, CachedBlob>>>,
+    capacity: Option<usize>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+/// The results of analyzing a blob that don't depend on attributes.
+#[derive(Clone, Debug)]
+// NOTE Without the git feature, blobs aren't cached, so the fields that only
+//      decide if a cached blob is reused aren't read.
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
+pub(crate) struct CachedBlob {
+    pub(crate) filepath: PathBuf,
+    pub(crate) language: Option<(Language, Detection)>,
+    pub(crate) size: usize,
+    pub(crate) lines: usize,
+    pub(crate) chars: usize,
+    pub(crate) binary: bool,
+    pub(crate) executable: bool,
+    /// The id of the `.gengo.toml` file that the blob was analyzed with.
+    pub(crate) config_id: Option<ObjectId>,
+    pub(crate) generated: bool,
+    pub(crate) documentation: bool,
+    pub(crate) vendored: bool,
+    pub(crate) embedded_languages: Vec<Language>,
+}
+
+impl BlobCache {
+    /// Creates an empty cache without a limit on its size.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty cache that holds at most `capacity` blobs.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            blobs: NonZeroUsize::new(capacity).map(|capacity| Mutex::new(LruCache::new(capacity))),
+            capacity: Some(capacity),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// The most blobs that are cached, if the size of the cache is limited.
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    /// The number of times that an analyzed blob was found in the cache, so
+    /// it wasn't read again.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// The number of times that an analyzed blob wasn't found in the cache.
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// The number of cached blobs.
+    pub fn len(&self) -> usize {
+        self.blobs
+            .as_ref()
+            .map_or(0, |blobs| blobs.lock().unwrap().len())
+    }
+
+    /// Checks if no blobs are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks if a blob is cached.
+    pub fn contains(&self, id: &ObjectId) -> bool {
+        self.blobs
+            .as_ref()
+            .is_some_and(|blobs| blobs.lock().unwrap().contains(id))
+    }
+
+    /// Removes a blob from the cache. Returns `true` if it was cached.
+    pub fn remove(&self, id: &ObjectId) -> bool {
+        self.blobs
+            .as_ref()
+            .is_some_and(|blobs| blobs.lock().unwrap().pop(id).is_some())
+    }
+
+    /// Removes all blobs from the cache.
+    pub fn clear(&self) {
+        if let Some(blobs) = &self.blobs {
+            blobs.lock().unwrap().clear();
+        }
+    }
+
+    #[cfg(feature = "git")]
+    pub(crate) fn get(
+        &self,
+        id: &ObjectId,
+        filepath: &Path,
+        executable: bool,
+        config_id: Option<ObjectId>,
+    ) -> Option<CachedBlob> {
+        // NOTE Only blobs that match are marked as recently used.
+        let blob = self.blobs.as_ref().and_then(|blobs| {
+            let mut blobs = blobs.lock().unwrap();
+            let matches = blobs.peek(id).is_some_and(|blob| {
+                blob.filepath == filepath
+                    && blob.executable == executable
+                    && blob.config_id == config_id
+            });
+            if !matches {
+                return None;
+            }
+            blobs.get(id).cloned()
+        });
+        let counter = if blob.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        blob
+    }
+
+    #[cfg(feature = "git")]
+    pub(crate) fn insert(&self, id: ObjectId, blob: CachedBlob) {
+        if let Some(blobs) = &self.blobs {
+            blobs.lock().unwrap().put(id, blob);
+        }
+    }
+}
+
+impl Default for BlobCache {
+    fn default() -> Self {
+        Self {
+            blobs: Some(Mutex::new(LruCache::unbounded())),
+            capacity: None,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "git"))]
+mod tests {
+    use super::*;
+
+    fn blob(filepath: &str) -> CachedBlob {
+        CachedBlob {
+            filepath: filepath.into(),
+            language: None,
+            size: 0,
+            lines: 0,
+            chars: 0,
+            binary: false,
+            executable: false,
+            config_id: None,
+            generated: false,
+            documentation: false,
+            vendored: false,
+            embedded_languages: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_get_requires_same_filepath() {
+        let cache = BlobCache::new();
+        let id = ObjectId::empty_blob(gix_hash::Kind::Sha1);
+        cache.insert(id, blob("foo.rs"));
+        assert!(cache.get(&id, Path::new("foo.rs"), false, None).is_some());
+        assert!(cache.get(&id, Path::new("bar.rs"), false, None).is_none());
+        assert!(cache.get(&id, Path::new("foo.rs"), true, None).is_none());
+    }
+
+    fn id(n: u8) -> ObjectId {
+        ObjectId::from([n; 20])
+    }
+
+    #[test]
+    fn test_least_recently_used_eviction() {
+        let cache = BlobCache::with_capacity(2);
+        assert_eq!(cache.capacity(), Some(2));
+        cache.insert(id(1), blob("a.rs"));
+        cache.insert(id(2), blob("b.rs"));
+        assert!(cache.get(&id(1), Path::new("a.rs"), false, None).is_some());
+        cache.insert(id(3), blob("c.rs"));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.contains(&id(1)), "a.rs was used more recently");
+        assert!(!cache.contains(&id(2)));
+        assert!(cache.contains(&id(3)));
+
+        cache.insert(id(1), blob("a.rs"));
+        cache.insert(id(4), blob("d.rs"));
+        assert!(cache.contains(&id(1)));
+        assert!(!cache.contains(&id(3)));
+
+        let cache = BlobCache::with_capacity(0);
+        cache.insert(id(1), blob("a.rs"));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_hits_and_misses() {
+        let cache = BlobCache::new();
+        assert_eq!(cache.capacity(), None);
+        cache.insert(id(1), blob("a.rs"));
+        assert!(cache.get(&id(1), Path::new("a.rs"), false, None).is_some());
+        assert!(cache.get(&id(1), Path::new("b.rs"), false, None).is_none());
+        assert!(cache.get(&id(2), Path::new("a.rs"), false, None).is_none());
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+    }
+
+    #[test]
+    fn test_eviction() {
+        let cache = BlobCache::new();
+        let id = ObjectId::empty_blob(gix_hash::Kind::Sha1);
+        assert!(cache.is_empty());
+        cache.insert(id, blob("foo.rs"));
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains(&id));
+        assert!(cache.remove(&id));
+        assert!(!cache.remove(&id));
+        cache.insert(id, blob("foo.rs"));
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,221 @@
+//! Reads per-repository settings from a `.gengo.toml` file.
+//!
+//! The file is read from the root of the tree of each revision that is
+//! analyzed, or from the root of the worktree when the worktree is analyzed,
+//! and supports these keys:
+//!
+//! ```toml
+//! # The limit for how many bytes are read from each file.
+//! read_limit = 65536
+//! # Globs for generated, documentation, vendored, and excluded paths.
+//! generated = ["dist/**"]
+//! documentation = ["manual/**"]
+//! vendored = ["third_party/**"]
+//! exclude = ["**/node_modules/**"]
+//! # Regular expressions for vendored paths, matched against the whole path.
+//! vendored_patterns = ['(^|/)generated/.*\.pb\.go$']
+//!
+//! # Additional languages, which take precedence over the built-in languages
+//! # on extension and filename collisions.
+//! [languages."My DSL"]
+//! category = "programming"
+//! color = "#123456"
+//! extensions = ["mydsl"]
+//! filenames = ["Dslfile"]
+//! ```
+use super::languages::Category;
+use super::LanguageSet;
+#[cfg(feature = "git")]
+use super::{Error, ErrorKind};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+#[cfg(feature = "git")]
+use std::io;
+#[cfg(feature = "git")]
+use std::path::Path;
+
+/// The name of the configuration file.
+#[cfg(feature = "git")]
+pub const FILENAME: &str = ".gengo.toml";
+
+/// Settings read from a `.gengo.toml` file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub read_limit: Option<usize>,
+    #[serde(default)]
+    pub generated: Vec<String>,
+    #[serde(default)]
+    pub documentation: Vec<String>,
+    #[serde(default)]
+    pub vendored: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    #[serde(default)]
+    pub vendored_patterns: Vec<String>,
+    #[serde(default)]
+    languages: BTreeMap<String, ConfigLanguage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigLanguage {
+    category: Category,
+    color: String,
+    #[serde(default)]
+    extensions: Vec<String>,
+    #[serde(default)]
+    filenames: Vec<String>,
+}
+
+impl Config {
+    /// Reads the configuration file at the root of a tree. Returns the id of
+    /// the file with the configuration, or `None` if there is no file.
+    #[cfg(feature = "git")]
+    pub fn from_tree(
+        repo: &gix::Repository,
+        tree_id: &gix::oid,
+    ) -> Result<Option<(gix::ObjectId, Self)>, Error> {
+        let tree = repo
+            .find_object(tree_id)
+            .map_err(invalid)?
+            .peel_to_tree()
+            .map_err(invalid)?;
+        let mut buf = Vec::new();
+        let Some(entry) = tree
+            .lookup_entry_by_path(FILENAME, &mut buf)
+            .map_err(invalid)?
+        else {
+            return Ok(None);
+        };
+        let blob = entry.object().map_err(invalid)?;
+        let config = Self::from_bytes(&blob.data)?;
+        Ok(Some((blob.id, config)))
+    }
+
+    /// Reads the configuration file at the root of a worktree. Returns the
+    /// id of the file with the configuration, or `None` if there is no file.
+    #[cfg(feature = "git")]
+    pub fn from_worktree(
+        dir: &Path,
+        object_hash: gix::hash::Kind,
+    ) -> Result<Option<(gix::ObjectId, Self)>, Error> {
+        let contents = match std::fs::read(dir.join(FILENAME)) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(invalid(err)),
+        };
+        let id = gix_object::compute_hash(object_hash, gix_object::Kind::Blob, &contents);
+        Ok(Some((id, Self::from_bytes(&contents)?)))
+    }
+
+    #[cfg(feature = "git")]
+    fn from_bytes(contents: &[u8]) -> Result<Self, Error> {
+        let toml = std::str::from_utf8(contents).map_err(invalid)?;
+        Self::from_toml(toml)
+    }
+
+    /// Parses the contents of a configuration file.
+    #[cfg(feature = "git")]
+    pub fn from_toml(toml: &str) -> Result<Self, Error> {
+        toml::from_str(toml).map_err(invalid)
+    }
+
+    /// Adds the configured languages to `languages`.
+    pub fn languages(&self, mut languages: LanguageSet) -> LanguageSet {
+        for (name, language) in self.languages.iter() {
+            let extensions: Vec<_> = language.extensions.iter().map(String::as_str).collect();
+            let filenames: Vec<_> = language.filenames.iter().map(String::as_str).collect();
+            languages = languages.language(
+                name,
+                language.category,
+                &language.color,
+                &extensions,
+                &filenames,
+            );
+        }
+        languages
+    }
+
+    /// Checks if any languages are configured.
+    pub fn has_languages(&self) -> bool {
+        !self.languages.is_empty()
+    }
+}
+
+#[cfg(feature = "git")]
+fn invalid<E>(err: E) -> Error
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    Error::with_source(ErrorKind::InvalidConfig, err).with_context(FILENAME)
+}
+
+#[cfg(all(test, feature = "git"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_toml() {
+        let config = Config::from_toml(
+            r##"
+            # Settings for this repository.
+            read_limit = 65_536
+            generated = ["dist/**", 'build/*.js'] # trailing comment
+            vendored = [
+                "third_party/**",
+            ]
+
+            [languages."My DSL"]
+            category = "programming"
+            color = "#123456"
+            extensions = ["mydsl"]
+            "##,
+        )
+        .unwrap();
+        assert_eq!(config.read_limit, Some(65536));
+        assert_eq!(config.generated, vec!["dist/**", "build/*.js"]);
+        assert_eq!(config.vendored, vec!["third_party/**"]);
+        assert!(config.documentation.is_empty());
+        let language = &config.languages["My DSL"];
+        assert_eq!(language.category, Category::Programming);
+        assert_eq!(language.color, "#123456");
+        assert_eq!(language.extensions, vec!["mydsl"]);
+    }
+
+    #[test]
+    fn test_from_toml_syntax() {
+        let config = Config::from_toml(
+            r##"
+            generated = ["""dist/**""", "caf\u00e9/**"]
+            vendored_patterns = ['''
+(^|/)third_party/''']
+            languages = { "My DSL" = { category = "data", color = "#000000" } }
+            "##,
+        )
+        .unwrap();
+        assert_eq!(config.generated, vec!["dist/**", "caf\u{e9}/**"]);
+        assert_eq!(config.vendored_patterns, vec!["(^|/)third_party/"]);
+        assert_eq!(config.languages["My DSL"].category, Category::Data);
+    }
+
+    #[test]
+    fn test_from_toml_errors() {
+        for toml in [
+            "read_limit = ",
+            "vendored = [\"a\"\n",
+            "read_limit = 1\nread_limit = 2",
+            "unknown = true",
+            "generated = \"dist/**\"",
+            "read_limit = 1.5",
+            "[languages.a]\ncategory = \"data\"\ncolor = \"#000000\"\n[languages.a]\n",
+            "[[languages]]\n",
+        ] {
+            let err = Config::from_toml(toml).err().unwrap();
+            assert!(
+                matches!(err.kind(), ErrorKind::InvalidConfig),
+                "{toml:?} should be invalid"
+            );
+        }
+    }
+}
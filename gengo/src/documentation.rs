@@ -1,14 +1,26 @@
 use super::GLOB_MATCH_OPTIONS;
 use glob::Pattern;
+use once_cell::sync::Lazy;
+use regex::RegexSet;
 use std::path::Path;
 
+/// The built-in patterns for documentation paths, which are the same as
+/// [Linguist's][documentation.yml]. They are matched against the whole path.
+///
+/// [documentation.yml]: https://github.com/github-linguist/linguist/blob/master/lib/linguist/documentation.yml
+static PATTERNS: Lazy<RegexSet> = Lazy::new(|| RegexSet::new(Documentation::patterns()).unwrap());
+
+#[derive(Clone)]
 pub struct Documentation {
     globs: Vec<Pattern>,
 }
 
 impl Documentation {
-    pub fn new() -> Self {
-        let globs = Self::globs();
+    /// Creates a new documentation matcher. Paths matching `extra_globs` are also
+    /// documentation.
+    pub fn new(extra_globs: Vec<Pattern>) -> Self {
+        let mut globs = extra_globs;
+        globs.extend(Self::globs());
 
         Self { globs }
     }
@@ -19,9 +31,13 @@ impl Documentation {
     }
 
     fn is_documentation_no_read<P: AsRef<Path>>(&self, filepath: P) -> bool {
+        let filepath = filepath.as_ref();
         self.globs
             .iter()
-            .any(|g| g.matches_path_with(filepath.as_ref(), GLOB_MATCH_OPTIONS))
+            .any(|g| g.matches_path_with(filepath, GLOB_MATCH_OPTIONS))
+            || filepath
+                .to_str()
+                .is_some_and(|path| PATTERNS.is_match(path))
     }
 
     fn is_documentation_with_read<P: AsRef<Path>>(&self, _filepath: P, _contents: &[u8]) -> bool {
@@ -44,6 +60,31 @@ impl Documentation {
         .map(|g| Pattern::new(g).unwrap())
         .collect()
     }
+
+    fn patterns() -> &'static [&'static str] {
+        &[
+            // Directories
+            r"^[Dd]ocs?/",
+            r"(^|/)[Dd]ocumentation/",
+            r"(^|/)[Gg]roovydoc/",
+            r"(^|/)[Jj]avadoc/",
+            r"^[Mm]an/",
+            r"^[Ee]xamples/",
+            r"^[Dd]emos?/",
+            r"(^|/)inst/doc/",
+            r"^[Ss]amples?/",
+            // Files
+            r"(^|/)CITATION(\.cff|(S)?(\.(bib|md))?)$",
+            r"(^|/)CHANGE(S|LOG)?(\.|$)",
+            r"(^|/)CONTRIBUTING(\.|$)",
+            r"(^|/)COPYING(\.|$)",
+            r"(^|/)INSTALL(\.|$)",
+            r"(^|/)LICEN[CS]E(\.|$)",
+            r"(^|/)[Ll]icen[cs]e(\.|$)",
+            r"(^|/)README(\.|$)",
+            r"(^|/)[Rr]eadme(\.|$)",
+        ]
+    }
 }
 
 #[cfg(test)]
@@ -67,10 +108,31 @@ mod tests {
         case("HACKING.md", true),
         case("README", true),
         case("README.txt", true),
-        case("README.md", true)
+        case("README.md", true),
+        case("Documentation/guide.md", true),
+        case("examples/hello.rs", true),
+        case("src/examples/hello.rs", false),
+        case("LICENSE-MIT", false),
+        case("LICENSE.md", true),
+        case("license.txt", true),
+        case("LiCENSE", false),
+        case("CONTRIBUTING.md", true),
+        case("src/Readme.rst", true),
+        case("src/reader.rs", false)
     )]
     fn test_is_documentation_no_read(filepath: &str, expected: bool) {
-        let documentation = Documentation::new();
+        let documentation = Documentation::new(Vec::new());
+        assert_eq!(documentation.is_documentation_no_read(filepath), expected);
+    }
+
+    #[rstest(
+        filepath,
+        expected,
+        case("manual/index.md", true),
+        case("src/manual/index.md", false)
+    )]
+    fn test_is_documentation_extra_globs(filepath: &str, expected: bool) {
+        let documentation = Documentation::new(vec![Pattern::new("manual/**").unwrap()]);
         assert_eq!(documentation.is_documentation_no_read(filepath), expected);
     }
 }
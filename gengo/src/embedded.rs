@@ -0,0 +1,122 @@
+//! Finds languages that are embedded in files of another language, like the
+//! scripts and styles of a Vue component.
+use super::encoding;
+use super::{Analyzers, Language};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Matches the opening `<script>`, `<style>`, and `<template>` tags of a Vue
+/// component or an HTML document, capturing the tag and its attributes.
+static BLOCK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)<(script|style|template)\b([^>]*)>").unwrap());
+
+/// Matches the `lang` or `type` attribute of a tag.
+static ATTRIBUTE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)\b(lang|type)\s*=\s*["']?([^"'\s>]+)"#).unwrap());
+
+/// Matches the info string of an opening Markdown code fence, like
+/// ```` ```rust ````.
+static FENCE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^ {0,3}(?:`{3,}|~{3,})[ \t]*([^\s`{,]+)").unwrap());
+
+/// Finds the languages embedded in a file of `language`, in the order they
+/// first appear. Only Vue, HTML, and Markdown files can embed languages.
+pub(crate) fn embedded_languages(
+    analyzers: &Analyzers,
+    language: &Language,
+    contents: &[u8],
+) -> Vec<Language> {
+    let text = encoding::decode(contents);
+    let names: Vec<_> = match language.name() {
+        "Vue" => blocks(&text, true),
+        "HTML" => blocks(&text, false),
+        "Markdown" => FENCE
+            .captures_iter(&text)
+            .map(|captures| captures[1].to_string())
+            .collect(),
+        _ => return Vec::new(),
+    };
+    let mut languages: Vec<Language> = Vec::new();
+    for found in names.iter().filter_map(|name| lookup(analyzers, name)) {
+        if found != language && !languages.contains(found) {
+            languages.push(found.clone());
+        }
+    }
+    languages
+}
+
+/// Gets the names of the languages of the blocks in a Vue component or an
+/// HTML document. Templates are only blocks in Vue components.
+fn blocks(text: &str, vue: bool) -> Vec<String> {
+    BLOCK
+        .captures_iter(text)
+        .filter_map(|captures| {
+            let tag = captures[1].to_lowercase();
+            let attribute = ATTRIBUTE
+                .captures_iter(&captures[2])
+                .find(|attribute| attribute[1].eq_ignore_ascii_case("lang"))
+                .or_else(|| ATTRIBUTE.captures(&captures[2]));
+            let value = attribute.map(|attribute| attribute[2].to_lowercase());
+            match (tag.as_str(), value.as_deref()) {
+                ("template", _) if !vue => None,
+                ("template", None) => Some("html".into()),
+                ("script", None | Some("module" | "text/javascript")) => Some("js".into()),
+                ("script", Some("application/json" | "application/ld+json" | "importmap")) => {
+                    Some("json".into())
+                }
+                ("style", None | Some("text/css")) => Some("css".into()),
+                // NOTE Other types, like `text/template`, aren't languages.
+                (_, Some(value)) if !value.contains('/') => Some(value.to_string()),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Gets a language by name, alias, or extension.
+fn lookup<'a>(analyzers: &'a Analyzers, name: &str) -> Option<&'a Language> {
+    analyzers.resolve(name).or_else(|| {
+        let found = analyzers.by_extension(format!("embedded.{}", name.to_lowercase()));
+        found.first().and_then(|key| analyzers.get(key))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        language,
+        contents,
+        expected,
+        case(
+            "Vue",
+            "<template lang=\"pug\">div</template>\n<script setup lang=\"ts\">\n</script>\n<style scoped>\n</style>\n",
+            &["Pug", "TypeScript", "CSS"]
+        ),
+        case(
+            "Vue",
+            "<template><div></div></template>\n<script>\n</script>\n<style lang=\"scss\">\n</style>\n",
+            &["HTML", "JavaScript", "Sass"]
+        ),
+        case(
+            "HTML",
+            "<style>p {}</style>\n<script type=\"module\"></script>\n<script type=\"text/template\"></script>\n<template></template>\n",
+            &["CSS", "JavaScript"]
+        ),
+        case(
+            "Markdown",
+            "# Example\n\n```rust\nfn main() {}\n```\n\n~~~ py\nprint(1)\n~~~\n\n```\nplain\n```\n\n```rust,ignore\n```\n\n```markdown\n```\n",
+            &["Rust", "Python"]
+        ),
+        case("Rust", "```js\n```\n", &[])
+    )]
+    fn test_embedded_languages(language: &str, contents: &str, expected: &[&str]) {
+        let analyzers = Analyzers::default();
+        let language = analyzers.get(language).unwrap();
+        let languages = embedded_languages(&analyzers, language, contents.as_bytes());
+        let names: Vec<_> = languages.iter().map(Language::name).collect();
+        assert_eq!(names, expected);
+    }
+}
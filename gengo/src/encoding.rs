@@ -0,0 +1,95 @@
+//! Decodes file contents to text.
+use encoding_rs::{Encoding, SHIFT_JIS, WINDOWS_1252};
+use std::borrow::Cow;
+
+/// Decodes contents to text, detecting the encoding.
+///
+/// The encoding is detected in this order:
+/// 1. by byte order mark
+/// 2. as UTF-8
+/// 3. as Shift-JIS, if it decodes without errors
+/// 4. as Windows-1252 (a superset of Latin-1), which always decodes
+///
+/// UTF-8 contents that were cut off in the middle of a character, such as by a
+/// read limit, are still decoded as UTF-8.
+pub(crate) fn decode(contents: &[u8]) -> Cow<'_, str> {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(contents) {
+        let (text, _) = encoding.decode_without_bom_handling(&contents[bom_length..]);
+        return text;
+    }
+    match std::str::from_utf8(contents) {
+        Ok(text) => return Cow::Borrowed(text),
+        // NOTE The contents end with an incomplete character.
+        Err(e) if e.error_len().is_none() => {
+            let text = std::str::from_utf8(&contents[..e.valid_up_to()]).unwrap_or_default();
+            return Cow::Borrowed(text);
+        }
+        Err(_) => {}
+    }
+    if let Some(text) = SHIFT_JIS.decode_without_bom_handling_and_without_replacement(contents) {
+        return text;
+    }
+    WINDOWS_1252.decode_without_bom_handling(contents).0
+}
+
+/// Counts the characters in contents, with the same encoding detection as
+/// `decode`. Contents are only decoded if they aren't UTF-8, so that counting
+/// doesn't allocate for most files.
+pub(crate) fn count_chars(contents: &[u8]) -> usize {
+    if contents.is_ascii() {
+        return contents.len();
+    }
+    if Encoding::for_bom(contents).is_none() {
+        let text = match std::str::from_utf8(contents) {
+            Ok(text) => Some(text),
+            Err(e) if e.error_len().is_none() => {
+                std::str::from_utf8(&contents[..e.valid_up_to()]).ok()
+            }
+            Err(_) => None,
+        };
+        if let Some(text) = text {
+            return text.chars().count();
+        }
+    }
+    decode(contents).chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        contents,
+        expected,
+        case(b"fn main() {}", "fn main() {}"),
+        case(b"\xEF\xBB\xBFfn main() {}", "fn main() {}"),
+        case(b"\xFF\xFEf\x00n\x00", "fn"),
+        case(b"\xFE\xFF\x00f\x00n", "fn"),
+        case(b"caf\xC3\xA9", "caf\u{e9}"),
+        case(b"caf\xC3", "caf"),
+        case(b"caf\xE9 au lait", "caf\u{e9} au lait"),
+        case(
+            b"\x82\xB1\x82\xF1\x82\xC9\x82\xBF\x82\xCD",
+            "\u{3053}\u{3093}\u{306b}\u{3061}\u{306f}"
+        )
+    )]
+    fn test_decode(contents: &[u8], expected: &str) {
+        assert_eq!(decode(contents), expected);
+    }
+
+    #[rstest(
+        contents,
+        expected,
+        case(b"fn main() {}", 12),
+        case(b"\xEF\xBB\xBFfn main() {}", 12),
+        case(b"\xFF\xFEf\x00n\x00", 2),
+        case(b"caf\xC3\xA9", 4),
+        case(b"caf\xC3", 3),
+        case(b"caf\xE9 au lait", 12),
+        case(b"\x82\xB1\x82\xF1\x82\xC9\x82\xBF\x82\xCD", 5)
+    )]
+    fn test_count_chars(contents: &[u8], expected: usize) {
+        assert_eq!(count_chars(contents), expected);
+    }
+}
@@ -3,8 +3,9 @@ use std::fmt;
 
 macro_rules! error_kind {
     ($($name:ident, $message:literal),*) => {
-        /// The kind of error that occurred.
-        #[derive(Debug)]
+        /// The kind of error that occurred. New kinds may be added, so
+        /// matches on this should have a wildcard arm.
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
         #[non_exhaustive]
         pub enum ErrorKind {
             $(
@@ -24,44 +25,100 @@ macro_rules! error_kind {
     };
 }
 
-error_kind!(NoRepository, "no repository found");
+error_kind!(
+    NoRepository,
+    "no repository found",
+    NoPath,
+    "path not found in revision",
+    BadRevision,
+    "revision could not be resolved to a tree",
+    NoWorktree,
+    "repository has no worktree",
+    NoCommits,
+    "repository has no commits",
+    ObjectMissing,
+    "object is missing from the repository",
+    Discovery,
+    "repository could not be opened",
+    InvalidGlob,
+    "glob is invalid",
+    InvalidRegex,
+    "regular expression is invalid",
+    InvalidLanguages,
+    "languages could not be added",
+    AttributesFile,
+    "attributes file could not be read",
+    InvalidConfig,
+    "configuration file is invalid"
+);
 
 impl ErrorTrait for ErrorKind {}
 
+/// An error from creating a `Gengo` instance or from analyzing a
+/// repository. Use `Error::kind` to handle each kind of error, and
+/// `Error::context` to find out what it is about, like the offending
+/// revision or path.
 #[derive(Debug)]
 pub struct Error {
     kind: ErrorKind,
-    source: Option<Box<dyn ErrorTrait>>,
+    source: Option<Box<dyn ErrorTrait + Send + Sync>>,
+    /// What the error is about, like the offending revision.
+    context: Option<String>,
 }
 
 impl Error {
+    /// Creates an error without a source.
     pub fn new(kind: ErrorKind) -> Self {
-        Self { kind, source: None }
+        Self {
+            kind,
+            source: None,
+            context: None,
+        }
     }
 
+    /// Creates an error that was caused by another error.
     pub fn with_source<E>(kind: ErrorKind, source: E) -> Self
     where
-        E: ErrorTrait + 'static,
+        E: ErrorTrait + Send + Sync + 'static,
     {
         Self {
             kind,
             source: Some(Box::new(source)),
+            context: None,
         }
     }
 
+    /// Adds what the error is about, which is included in the message.
+    pub(crate) fn with_context<S: Into<String>>(mut self, context: S) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// The kind of error that occurred.
     pub fn kind(&self) -> &ErrorKind {
         &self.kind
     }
+
+    /// What the error is about, like the revision that couldn't be resolved,
+    /// or the path that wasn't found.
+    pub fn context(&self) -> Option<&str> {
+        self.context.as_deref()
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", &self.kind)
+        match &self.context {
+            Some(context) => write!(f, "{}: {}", &self.kind, context),
+            None => write!(f, "{}", &self.kind),
+        }
     }
 }
 
 impl ErrorTrait for Error {
     fn source(&self) -> Option<&(dyn ErrorTrait + 'static)> {
-        self.source.as_ref().map(|s| s.as_ref())
+        self.source
+            .as_ref()
+            .map(|s| s.as_ref() as &(dyn ErrorTrait + 'static))
     }
 }
@@ -0,0 +1,41 @@
+use super::{Entry, Language};
+
+/// Describes the signals that were used to analyze a single file.
+#[derive(Debug)]
+pub struct Explanation {
+    pub(crate) shebang_matches: Vec<Language>,
+    pub(crate) filename_matches: Vec<Language>,
+    pub(crate) extension_matches: Vec<Language>,
+    pub(crate) attributes: Vec<(String, String)>,
+    pub(crate) entry: Option<Entry>,
+}
+
+impl Explanation {
+    /// The languages that matched the shebang (`#!`).
+    pub fn shebang_matches(&self) -> &[Language] {
+        &self.shebang_matches
+    }
+
+    /// The languages that matched the filename or a filepath pattern.
+    pub fn filename_matches(&self) -> &[Language] {
+        &self.filename_matches
+    }
+
+    /// The languages that matched the extension.
+    pub fn extension_matches(&self) -> &[Language] {
+        &self.extension_matches
+    }
+
+    /// The `gengo-*` attributes, and git's `text` attribute, that were
+    /// specified for the file, as pairs of names and values. The value of an
+    /// attribute without a value is `set` or `unset`.
+    pub fn attributes(&self) -> &[(String, String)] {
+        &self.attributes
+    }
+
+    /// The result of the analysis, including the final language and if the
+    /// file is detectable. This is `None` if no language was identified.
+    pub fn entry(&self) -> Option<&Entry> {
+        self.entry.as_ref()
+    }
+}
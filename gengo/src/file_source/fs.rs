@@ -0,0 +1,250 @@
+//! Reads files from a directory on the filesystem.
+use super::{FileSource, SymlinkPolicy};
+use gix_glob::pattern::Case;
+use gix_ignore::Search;
+use std::borrow::Cow;
+#[cfg(unix)]
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Walks a directory, which does not need to be a git repository, and
+/// reads its files.
+///
+/// File contents are only read as the iterator advances, so at most one
+/// file is held in memory at a time.
+///
+/// When ignored files are skipped, the `.gitignore` files in every directory
+/// and `.git/info/exclude` are respected, like when git adds files.
+///
+/// # Example
+///
+/// ```no_run
+/// use gengo::file_source::{FileSource, Fs};
+/// let fs = Fs::new("path/to/directory", true);
+/// for (path, contents) in fs.files().unwrap() {
+///     println!("{}: {} bytes", path.display(), contents.len());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Fs {
+    root: PathBuf,
+    gitignore: bool,
+    symlinks: SymlinkPolicy,
+}
+
+impl Fs {
+    /// Creates a new source rooted at `root`. If `gitignore` is `true`,
+    /// paths matching `.gitignore` files in the directory, or
+    /// `.git/info/exclude`, are skipped.
+    pub fn new<P: AsRef<Path>>(root: P, gitignore: bool) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            gitignore,
+            symlinks: SymlinkPolicy::default(),
+        }
+    }
+
+    /// Sets how symbolic links are handled. If this is not set, links are
+    /// followed with `SymlinkPolicy::FollowOnce`.
+    pub fn symlinks(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlinks = policy;
+        self
+    }
+
+    /// Sets if ignored files are included. This is the opposite of the
+    /// `gitignore` flag of `Fs::new`.
+    pub fn include_ignored(mut self, include: bool) -> Self {
+        self.gitignore = !include;
+        self
+    }
+
+    /// The root of the directory that is walked.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Walks the directory like `files`, but only yields the paths of the
+    /// files, relative to the root, without reading them.
+    #[cfg(feature = "git")]
+    pub(crate) fn paths(&self) -> Paths<'_> {
+        Paths(self.iter())
+    }
+
+    fn iter(&self) -> Iter<'_> {
+        let walker = WalkDir::new(&self.root)
+            .follow_links(self.symlinks == SymlinkPolicy::FollowOnce)
+            .sort_by_file_name()
+            .into_iter();
+        let ignore = self.gitignore.then(Search::default);
+        Iter {
+            root: &self.root,
+            walker,
+            ignore,
+            symlinks: self.symlinks,
+            #[cfg(unix)]
+            visited_dirs: HashSet::new(),
+            buf: Vec::new(),
+        }
+    }
+}
+
+impl<'repo> FileSource<'repo> for Fs {
+    type Iter = Iter<'repo>;
+
+    fn files(&'repo self) -> crate::Result<Self::Iter> {
+        Ok(self.iter())
+    }
+}
+
+/// Iterates over the files in a directory.
+pub struct Iter<'repo> {
+    root: &'repo Path,
+    walker: walkdir::IntoIter,
+    /// Patterns from `.git/info/exclude` and the `.gitignore` files found so
+    /// far, if they are respected.
+    ignore: Option<Search>,
+    symlinks: SymlinkPolicy,
+    /// The devices and inodes of the directories walked so far.
+    #[cfg(unix)]
+    visited_dirs: HashSet<(u64, u64)>,
+    buf: Vec<u8>,
+}
+
+impl<'repo> Iter<'repo> {
+    /// Checks if a path relative to the root is ignored.
+    fn is_ignored(&self, relative_path: &Path, is_dir: bool) -> bool {
+        let Some(ignore) = self.ignore.as_ref() else {
+            return false;
+        };
+        let Ok(relative_path) = gix_path::try_into_bstr(relative_path) else {
+            return false;
+        };
+        let relative_path = gix_path::to_unix_separators_on_windows(relative_path);
+        ignore
+            .pattern_matching_relative_path(relative_path.as_ref(), Some(is_dir), Case::Sensitive)
+            .is_some_and(|m| !m.pattern.is_negative())
+    }
+
+    /// Checks if a directory was already walked through another path. This
+    /// is only known on Unix, where directories are identified by inode.
+    fn is_visited(&mut self, entry: &walkdir::DirEntry) -> bool {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            let Ok(metadata) = entry.metadata() else {
+                return false;
+            };
+            !self.visited_dirs.insert((metadata.dev(), metadata.ino()))
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = entry;
+            false
+        }
+    }
+
+    /// Reads the contents of a file, or the target of a symbolic link that
+    /// is treated as a file.
+    fn read(&self, entry: &walkdir::DirEntry) -> Option<Vec<u8>> {
+        if entry.file_type().is_symlink() {
+            if self.symlinks != SymlinkPolicy::TreatAsFile {
+                return None;
+            }
+            let target = std::fs::read_link(entry.path()).ok()?;
+            let target = gix_path::try_into_bstr(target).ok()?;
+            return Some(gix_path::to_unix_separators_on_windows(target).to_vec());
+        }
+        std::fs::read(entry.path()).ok()
+    }
+
+    /// Advances to the next file that isn't ignored, without reading it.
+    fn next_file(&mut self) -> Option<(PathBuf, walkdir::DirEntry)> {
+        loop {
+            // NOTE Errors, including symlink loops, skip the offending entry.
+            let Ok(entry) = self.walker.next()? else {
+                continue;
+            };
+            let Ok(relative_path) = entry.path().strip_prefix(self.root) else {
+                continue;
+            };
+            let relative_path = relative_path.to_path_buf();
+            let file_type = entry.file_type();
+
+            if file_type.is_dir() {
+                if entry.depth() == 0 {
+                    self.is_visited(&entry);
+                    self.load_exclude();
+                    self.load_gitignore(entry.path());
+                } else if entry.file_name() == ".git"
+                    || self.is_ignored(&relative_path, true)
+                    || self.is_visited(&entry)
+                {
+                    self.walker.skip_current_dir();
+                } else {
+                    self.load_gitignore(entry.path());
+                }
+                continue;
+            }
+            let is_file = file_type.is_file()
+                || (file_type.is_symlink() && self.symlinks == SymlinkPolicy::TreatAsFile);
+            if !is_file || self.is_ignored(&relative_path, false) {
+                continue;
+            }
+            return Some((relative_path, entry));
+        }
+    }
+
+    /// Adds the patterns of the `.gitignore` file in `dir`, if it exists.
+    fn load_gitignore(&mut self, dir: &Path) {
+        self.load_patterns(dir.join(".gitignore"), Some(self.root));
+    }
+
+    /// Adds the patterns of the repository's `.git/info/exclude` file, if it
+    /// exists. Like the root `.gitignore`, its patterns are relative to the
+    /// root.
+    fn load_exclude(&mut self) {
+        self.load_patterns(self.root.join(".git").join("info").join("exclude"), None);
+    }
+
+    /// Adds the patterns of an ignore file. Patterns that are added later
+    /// take precedence.
+    fn load_patterns(&mut self, source: PathBuf, root: Option<&Path>) {
+        let Some(ignore) = self.ignore.as_mut() else {
+            return;
+        };
+        if let Ok(Some(list)) =
+            gix_glob::search::pattern::List::from_file(source, root, true, &mut self.buf)
+        {
+            ignore.patterns.push(list);
+        }
+    }
+}
+
+impl<'repo> Iterator for Iter<'repo> {
+    type Item = (Cow<'repo, Path>, Cow<'repo, [u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (relative_path, entry) = self.next_file()?;
+            let Some(contents) = self.read(&entry) else {
+                continue;
+            };
+            return Some((Cow::Owned(relative_path), Cow::Owned(contents)));
+        }
+    }
+}
+
+/// Iterates over the paths of the files in a directory.
+#[cfg(feature = "git")]
+pub(crate) struct Paths<'repo>(Iter<'repo>);
+
+#[cfg(feature = "git")]
+impl Iterator for Paths<'_> {
+    type Item = PathBuf;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_file().map(|(path, _)| path)
+    }
+}
@@ -0,0 +1,153 @@
+//! Reads files from a revision of a git repository.
+use super::{FileSource, SymlinkPolicy};
+use crate::Error;
+use gix::bstr::{BStr, ByteSlice};
+use std::borrow::Cow;
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// Reads the blobs of the tree at a git revision.
+///
+/// Only tracked files are in the tree, so ignored files are never read. This
+/// is consistent with `Fs` when it skips ignored files.
+///
+/// # Example
+///
+/// ```no_run
+/// use gengo::file_source::{FileSource, Git};
+/// let git = Git::new("path/to/repo", "HEAD").unwrap();
+/// for (path, contents) in git.files().unwrap() {
+///     println!("{}: {} bytes", path.display(), contents.len());
+/// }
+/// ```
+pub struct Git {
+    repository: gix::ThreadSafeRepository,
+    rev: String,
+    symlinks: SymlinkPolicy,
+}
+
+impl Git {
+    /// Opens the repository containing `path`, reading files from `rev`.
+    ///
+    /// Returns an error with `ErrorKind::NoRepository` if no repository
+    /// contains the path.
+    pub fn new<P: AsRef<Path>>(path: P, rev: &str) -> Result<Self, Error> {
+        let repository = crate::discover(path)?;
+        Ok(Self {
+            repository: repository.into_sync(),
+            rev: rev.to_string(),
+            symlinks: SymlinkPolicy::Skip,
+        })
+    }
+
+    /// Sets how symbolic links are handled. If this is not set, links are
+    /// skipped with `SymlinkPolicy::Skip`. `SymlinkPolicy::FollowOnce` reads
+    /// the file that a link targets if it is in the same tree. Links to
+    /// directories and other links are skipped.
+    pub fn symlinks(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlinks = policy;
+        self
+    }
+}
+
+impl<'repo> FileSource<'repo> for Git {
+    type Iter = Iter<'repo>;
+
+    fn files(&'repo self) -> crate::Result<Self::Iter> {
+        use gix::index::entry::Mode;
+
+        let repo = self.repository.to_thread_local();
+        let tree_id = crate::tree_id(&repo, &self.rev)?;
+        let index = crate::index_from_tree(&repo, &tree_id)?;
+        let (entries, path_storage) = index.into_entries();
+        // NOTE Submodules (commits) aren't blobs with contents to analyze.
+        let entries = entries
+            .into_iter()
+            .filter(|e| match e.mode {
+                Mode::FILE | Mode::FILE_EXECUTABLE => true,
+                Mode::SYMLINK => self.symlinks != SymlinkPolicy::Skip,
+                _ => false,
+            })
+            .collect();
+        Ok(Iter {
+            repo,
+            entries,
+            path_storage,
+            symlinks: self.symlinks,
+            cursor: 0,
+            source: PhantomData,
+        })
+    }
+}
+
+/// Iterates over the blobs of a tree.
+pub struct Iter<'repo> {
+    repo: gix::Repository,
+    entries: Vec<gix::index::Entry>,
+    path_storage: gix::index::PathStorage,
+    symlinks: SymlinkPolicy,
+    cursor: usize,
+    source: PhantomData<&'repo Git>,
+}
+
+impl<'repo> Iter<'repo> {
+    /// Finds the file that a symbolic link at `path` targets. Returns
+    /// `None` if the target isn't a file in the tree.
+    fn target(&self, path: &BStr, target: &[u8]) -> Option<gix::ObjectId> {
+        use gix::index::entry::Mode;
+
+        // NOTE Absolute targets are outside of the tree.
+        if target.starts_with(b"/") {
+            return None;
+        }
+        let mut components: Vec<&[u8]> = path.split_str("/").collect();
+        components.pop();
+        for component in target.split_str("/") {
+            match component {
+                b"" | b"." => {}
+                b".." => {
+                    components.pop()?;
+                }
+                component => components.push(component),
+            }
+        }
+        let target = components.join(&b'/');
+        let index = self
+            .entries
+            .binary_search_by(|e| e.path_in(&self.path_storage).cmp(target.as_bstr()))
+            .ok()?;
+        let entry = &self.entries[index];
+        matches!(entry.mode, Mode::FILE | Mode::FILE_EXECUTABLE).then_some(entry.id)
+    }
+}
+
+impl<'repo> Iterator for Iter<'repo> {
+    type Item = (Cow<'repo, Path>, Cow<'repo, [u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = self.entries.get(self.cursor)?;
+            self.cursor += 1;
+            let Ok(path) = gix::path::try_from_bstr(entry.path_in(&self.path_storage)) else {
+                continue;
+            };
+            let Ok(blob) = self.repo.find_object(entry.id) else {
+                continue;
+            };
+            let mut data = blob.detach().data;
+            if entry.mode == gix::index::entry::Mode::SYMLINK
+                && self.symlinks == SymlinkPolicy::FollowOnce
+            {
+                let Some(target) = self.target(entry.path_in(&self.path_storage), &data) else {
+                    continue;
+                };
+                let Ok(blob) = self.repo.find_object(target) else {
+                    continue;
+                };
+                data = blob.detach().data;
+            }
+            let path = Cow::Owned(path.into_owned());
+            return Some((path, Cow::Owned(data)));
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Reads files that are held in memory.
+use super::FileSource;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+/// Holds files in memory, which is useful for testing and for analyzing
+/// generated contents without writing them to disk.
+///
+/// Paths and contents can either be owned or borrowed, so files that are
+/// already in memory don't need to be copied.
+///
+/// # Example
+///
+/// ```
+/// use gengo::file_source::{FileSource, Memory};
+/// let mut memory = Memory::default();
+/// memory.push("src/main.rs", b"fn main() {}".to_vec());
+/// for (path, contents) in memory.files().unwrap() {
+///     println!("{}: {} bytes", path.display(), contents.len());
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct Memory<'a> {
+    files: Vec<(Cow<'a, Path>, Cow<'a, [u8]>)>,
+}
+
+impl<'a> Memory<'a> {
+    /// Creates a new source from pairs of paths and contents. Files are
+    /// yielded in the given order.
+    pub fn new<P, C, I>(files: I) -> Self
+    where
+        P: Into<Cow<'a, Path>>,
+        C: Into<Cow<'a, [u8]>>,
+        I: IntoIterator<Item = (P, C)>,
+    {
+        let files = files
+            .into_iter()
+            .map(|(path, contents)| (path.into(), contents.into()))
+            .collect();
+        Self { files }
+    }
+
+    /// Adds a file.
+    pub fn push<P: Into<PathBuf>, C: Into<Cow<'a, [u8]>>>(&mut self, path: P, contents: C) {
+        self.files.push((Cow::Owned(path.into()), contents.into()));
+    }
+}
+
+impl<'repo, 'a: 'repo> FileSource<'repo> for Memory<'a> {
+    type Iter = Iter<'repo>;
+
+    fn files(&'repo self) -> crate::Result<Self::Iter> {
+        Ok(Iter {
+            files: self.files.iter(),
+        })
+    }
+}
+
+/// Iterates over the files held in memory.
+pub struct Iter<'repo> {
+    files: std::slice::Iter<'repo, (Cow<'repo, Path>, Cow<'repo, [u8]>)>,
+}
+
+impl<'repo> Iterator for Iter<'repo> {
+    type Item = (Cow<'repo, Path>, Cow<'repo, [u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (path, contents) = self.files.next()?;
+        Some((Cow::Borrowed(path), Cow::Borrowed(contents)))
+    }
+}
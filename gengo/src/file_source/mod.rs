@@ -0,0 +1,42 @@
+//! Sources of files that can be analyzed.
+use std::borrow::Cow;
+use std::path::Path;
+
+pub use fs::Fs;
+#[cfg(feature = "git")]
+pub use git::Git;
+pub use memory::Memory;
+#[cfg(feature = "tar")]
+pub use tar::Tar;
+
+pub mod fs;
+#[cfg(feature = "git")]
+pub mod git;
+pub mod memory;
+#[cfg(feature = "tar")]
+pub mod tar;
+
+/// Provides the files to analyze.
+pub trait FileSource<'repo> {
+    /// Iterates over each file's path and contents.
+    type Iter: Iterator<Item = (Cow<'repo, Path>, Cow<'repo, [u8]>)>;
+
+    /// Returns an iterator over the files of this source. Paths are
+    /// relative to the root of the source.
+    fn files(&'repo self) -> crate::Result<Self::Iter>;
+}
+
+/// How a source handles symbolic links.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SymlinkPolicy {
+    /// Ignores symbolic links.
+    Skip,
+    /// Reads the file that a symbolic link targets at the link's path.
+    /// Directories are only walked once, so cycles are ignored.
+    #[default]
+    FollowOnce,
+    /// Reads a symbolic link as a file whose contents are the target path,
+    /// which is how git stores symbolic links.
+    TreatAsFile,
+}
@@ -0,0 +1,225 @@
+//! Reads files from a tar archive.
+use super::FileSource;
+use bstr::ByteSlice;
+use flate2::read::MultiGzDecoder;
+use std::borrow::Cow;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// The size of a header, and the alignment of each member's contents.
+const BLOCK_SIZE: usize = 512;
+
+/// The magic bytes that start a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Reads the regular files of a tar archive, without extracting it.
+/// Directories, links, and device nodes are skipped.
+///
+/// Archives that are compressed with gzip are decompressed when the source
+/// is created, including archives with multiple gzip members. The whole
+/// archive is held in memory, and the contents of each file are borrowed
+/// from it. Truncated archives are rejected when the source is created.
+///
+/// # Example
+///
+/// ```no_run
+/// use gengo::file_source::{FileSource, Tar};
+/// let tar = Tar::open("path/to/archive.tar.gz").unwrap();
+/// for (path, contents) in tar.files().unwrap() {
+///     println!("{}: {} bytes", path.display(), contents.len());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Tar {
+    archive: Vec<u8>,
+}
+
+impl Tar {
+    /// Creates a new source from the bytes of an archive. If the bytes
+    /// start with the gzip magic bytes, they are decompressed.
+    ///
+    /// Returns an error if the archive is truncated, or if a header is
+    /// invalid.
+    pub fn new(archive: Vec<u8>) -> io::Result<Self> {
+        let archive = if archive.starts_with(&GZIP_MAGIC) {
+            let mut decompressed = Vec::new();
+            MultiGzDecoder::new(archive.as_slice()).read_to_end(&mut decompressed)?;
+            decompressed
+        } else {
+            archive
+        };
+        let mut offset = 0;
+        while let Some(member) = read_member(&archive, offset)? {
+            offset = member.next;
+        }
+        Ok(Self { archive })
+    }
+
+    /// Reads an archive from a file.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(std::fs::read(path)?)
+    }
+}
+
+impl<'repo> FileSource<'repo> for Tar {
+    type Iter = Iter<'repo>;
+
+    fn files(&'repo self) -> crate::Result<Self::Iter> {
+        Ok(Iter {
+            archive: &self.archive,
+            offset: 0,
+        })
+    }
+}
+
+/// Iterates over the regular files of a tar archive.
+pub struct Iter<'repo> {
+    archive: &'repo [u8],
+    offset: usize,
+}
+
+impl<'repo> Iter<'repo> {
+    /// Reads the next member, returning its header and contents. Returns
+    /// `None` at the end of the archive.
+    fn next_member(&mut self) -> Option<(&'repo [u8], &'repo [u8])> {
+        // NOTE The archive was checked when the source was created, so
+        //      there are no errors.
+        let member = read_member(self.archive, self.offset).ok()??;
+        self.offset = member.next;
+        Some((member.header, member.contents))
+    }
+}
+
+impl<'repo> Iterator for Iter<'repo> {
+    type Item = (Cow<'repo, Path>, Cow<'repo, [u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // NOTE Long paths are stored in a member before the one they belong to.
+        let mut long_path: Option<&'repo [u8]> = None;
+        loop {
+            let (header, contents) = self.next_member()?;
+            match header[156] {
+                b'L' => long_path = Some(until_nul(contents)),
+                b'x' => long_path = pax_path(contents).or(long_path),
+                b'0' | b'\0' | b'7' => {
+                    let path = match long_path.take() {
+                        Some(path) => Cow::Borrowed(path),
+                        None => header_path(header),
+                    };
+                    let Some(path) = to_path(path) else {
+                        continue;
+                    };
+                    return Some((path, Cow::Borrowed(contents)));
+                }
+                _ => long_path = None,
+            }
+        }
+    }
+}
+
+/// A member of an archive.
+struct Member<'a> {
+    header: &'a [u8],
+    contents: &'a [u8],
+    /// The offset of the next member.
+    next: usize,
+}
+
+/// Reads the member at `offset`. Returns `None` at the end of the archive,
+/// and an error if the archive is truncated.
+fn read_member(archive: &[u8], offset: usize) -> io::Result<Option<Member<'_>>> {
+    if offset >= archive.len() {
+        return Ok(None);
+    }
+    let truncated = || io::Error::new(io::ErrorKind::UnexpectedEof, "truncated tar archive");
+    let header = archive
+        .get(offset..offset + BLOCK_SIZE)
+        .ok_or_else(truncated)?;
+    // NOTE The archive ends with blocks of zeros.
+    if header.iter().all(|&b| b == 0) {
+        return Ok(None);
+    }
+    let size = parse_size(&header[124..136])
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid tar header size"))?;
+    let start = offset + BLOCK_SIZE;
+    let contents = start
+        .checked_add(size)
+        .and_then(|end| archive.get(start..end))
+        .ok_or_else(truncated)?;
+    let next = start + size.div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+    Ok(Some(Member {
+        header,
+        contents,
+        next,
+    }))
+}
+
+/// Gets the path of a member from its header, joining the prefix of USTAR
+/// headers to the name.
+fn header_path(header: &[u8]) -> Cow<'_, [u8]> {
+    let name = until_nul(&header[..100]);
+    let prefix = until_nul(&header[345..500]);
+    // NOTE GNU headers use the prefix field for other data.
+    if &header[257..263] != b"ustar\0" || prefix.is_empty() {
+        return Cow::Borrowed(name);
+    }
+    let mut path = prefix.to_vec();
+    path.push(b'/');
+    path.extend_from_slice(name);
+    Cow::Owned(path)
+}
+
+/// Gets the `path` record of a PAX extended header.
+fn pax_path(contents: &[u8]) -> Option<&[u8]> {
+    let mut records = contents;
+    while !records.is_empty() {
+        // NOTE Each record is "<length> <key>=<value>\n", where the length
+        //      includes the whole record.
+        let space = records.find_byte(b' ')?;
+        let len: usize = records[..space].to_str().ok()?.parse().ok()?;
+        let record = records.get(space + 1..len)?.strip_suffix(b"\n")?;
+        if let Some(path) = record.strip_prefix(b"path=") {
+            return Some(path);
+        }
+        records = &records[len..];
+    }
+    None
+}
+
+/// Converts the path of a member to a relative path. Returns `None` for
+/// empty paths and directories.
+fn to_path(path: Cow<'_, [u8]>) -> Option<Cow<'_, Path>> {
+    fn strip(path: &[u8]) -> Option<&Path> {
+        let path = path.strip_prefix(b"./").unwrap_or(path);
+        if path.is_empty() || path.ends_with(b"/") {
+            return None;
+        }
+        gix_path::try_from_byte_slice(path).ok()
+    }
+    match path {
+        Cow::Borrowed(path) => strip(path).map(Cow::Borrowed),
+        Cow::Owned(path) => strip(&path).map(|path| Cow::Owned(path.to_path_buf())),
+    }
+}
+
+/// Parses the size field of a header, which is either octal or, for large
+/// sizes, big-endian base-256.
+fn parse_size(field: &[u8]) -> Option<usize> {
+    if field[0] & 0x80 != 0 {
+        let mut size: usize = usize::from(field[0] & 0x7f);
+        for &b in &field[1..] {
+            size = size.checked_mul(256)?.checked_add(usize::from(b))?;
+        }
+        return Some(size);
+    }
+    let field = until_nul(field).trim().to_str().ok()?;
+    if field.is_empty() {
+        return Some(0);
+    }
+    usize::from_str_radix(field, 8).ok()
+}
+
+/// Gets the bytes before the first NUL.
+fn until_nul(bytes: &[u8]) -> &[u8] {
+    bytes.find_byte(b'\0').map_or(bytes, |end| &bytes[..end])
+}
@@ -1,16 +1,36 @@
+use super::encoding;
 use super::GLOB_MATCH_OPTIONS;
+use bstr::ByteSlice;
 use glob::Pattern;
+use once_cell::sync::Lazy;
+use regex::RegexSet;
 use std::path::Path;
 
+/// The number of lines at the start of a file that are checked for the
+/// banners that generators emit.
+const HEADER_LINES: usize = 10;
+
+/// Matches a line of a banner that a generator emits.
+static SIGNATURES: Lazy<RegexSet> = Lazy::new(|| RegexSet::new(Generated::signatures()).unwrap());
+
+#[derive(Clone)]
 pub struct Generated {
     globs: Vec<Pattern>,
+    signatures: Vec<String>,
 }
 
 impl Generated {
-    pub fn new() -> Self {
-        let globs = Self::globs();
+    /// Creates a new generated matcher. Paths matching `extra_globs` are also
+    /// generated, as are files with any of `extra_signatures` in their first
+    /// lines.
+    pub fn new(extra_globs: Vec<Pattern>, extra_signatures: Vec<String>) -> Self {
+        let mut globs = extra_globs;
+        globs.extend(Self::globs());
 
-        Self { globs }
+        Self {
+            globs,
+            signatures: extra_signatures,
+        }
     }
 
     pub fn is_generated<P: AsRef<Path>>(&self, filepath: P, contents: &[u8]) -> bool {
@@ -24,15 +44,35 @@ impl Generated {
     }
 
     fn is_generated_with_read<P: AsRef<Path>>(&self, _filepath: P, contents: &[u8]) -> bool {
-        self.likely_minified(contents)
+        self.has_signature(contents) || self.likely_minified(contents)
+    }
+
+    /// Checks if the first lines of the contents are a banner that
+    /// generators emit, like `// Code generated by ... DO NOT EDIT.`
+    fn has_signature(&self, contents: &[u8]) -> bool {
+        // NOTE Only the header is decoded, since banners are at the top.
+        let end = contents
+            .find_iter(b"\n")
+            .nth(HEADER_LINES - 1)
+            .unwrap_or(contents.len());
+        encoding::decode(&contents[..end])
+            .lines()
+            .take(HEADER_LINES)
+            .any(|line| {
+                SIGNATURES.is_match(line)
+                    || self
+                        .signatures
+                        .iter()
+                        .any(|signature| line.contains(signature.as_str()))
+            })
     }
 
     fn likely_minified(&self, contents: &[u8]) -> bool {
         // NOTE If the first 10 lines are really long, it's probably minified.
-        contents
-            .split(|&b| b == b'\n')
+        encoding::decode(contents)
+            .lines()
             .take(10)
-            .any(|line| line.len() > 250)
+            .any(|line| line.chars().count() > 250)
     }
 
     fn globs() -> Vec<Pattern> {
@@ -47,6 +87,19 @@ impl Generated {
         .map(|s| Pattern::new(s).unwrap())
         .collect()
     }
+
+    fn signatures() -> Vec<&'static str> {
+        vec![
+            // NOTE Go's convention, also used by other generators.
+            r"^// Code generated .* DO NOT EDIT\.$",
+            // NOTE Used by Meta's tools, like Relay and Hack codegen.
+            r"^\s*(//|#|\*|/\*+|--)\s*@generated\b",
+            // NOTE protoc
+            r"^(//|#) Generated by the protocol buffer compiler\.",
+            r"^\s*(\*|#|//)\s*Autogenerated by Thrift Compiler\b",
+            r"^\s*//\s*<auto-generated",
+        ]
+    }
 }
 
 #[cfg(test)]
@@ -69,13 +122,65 @@ mod tests {
         case("myapp/migrations/0001_initial.py", true)
     )]
     fn test_is_generated_no_read(filepath: &str, expected: bool) {
-        let generated = Generated::new();
+        let generated = Generated::new(Vec::new(), Vec::new());
         assert_eq!(generated.is_generated_no_read(filepath), expected);
     }
 
+    #[rstest(
+        filepath,
+        expected,
+        case("api/service.pb.go", true),
+        case("api/service.go", false)
+    )]
+    fn test_is_generated_extra_globs(filepath: &str, expected: bool) {
+        let generated = Generated::new(vec![Pattern::new("**/*.pb.go").unwrap()], Vec::new());
+        assert_eq!(generated.is_generated_no_read(filepath), expected);
+    }
+
+    #[rstest(
+        contents,
+        expected,
+        case(
+            "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n",
+            true
+        ),
+        case("/**\n * @generated\n */\nexport const x = 1;\n", true),
+        case("// Generated by the protocol buffer compiler.  DO NOT EDIT!\n", true),
+        case("/**\n * Autogenerated by Thrift Compiler (0.19.0)\n */\n", true),
+        case("// <auto-generated>\n// </auto-generated>\n", true),
+        case(
+            "// Copyright 2024 Someone\n\n// Code generated by stringer; DO NOT EDIT.\n",
+            true
+        ),
+        case("// Written by hand.\nfn main() {}\n", false),
+        case("// Please DO NOT EDIT this by hand.\nfn main() {}\n", false),
+        case("const BANNER: &str = \"@generated\";\n", false),
+        case(
+            "fn main() {}\n\n\n\n\n\n\n\n\n\n// Code generated by hand. DO NOT EDIT.\n",
+            false
+        )
+    )]
+    fn test_has_signature(contents: &str, expected: bool) {
+        let generated = Generated::new(Vec::new(), Vec::new());
+        assert_eq!(generated.has_signature(contents.as_bytes()), expected);
+    }
+
+    #[test]
+    fn test_has_extra_signature() {
+        let generated = Generated::new(Vec::new(), vec!["Generated by in-house-gen".into()]);
+        assert!(generated.has_signature(b"# Generated by in-house-gen v2\n"));
+        assert!(!generated.has_signature(b"# Written by hand\n"));
+        let late = [
+            &b"\n".repeat(HEADER_LINES)[..],
+            b"# Generated by in-house-gen v2\n",
+        ]
+        .concat();
+        assert!(!generated.has_signature(&late));
+    }
+
     #[test]
     fn test_likely_minified() {
-        let generated = Generated::new();
+        let generated = Generated::new(Vec::new(), Vec::new());
         let header: Vec<u8> = b"/*!\n  * This is my license etc etc\n */".to_vec();
         let contents = b"console.log('hello, world!');".repeat(50);
         let contents = [header, contents].concat();
@@ -1,20 +1,58 @@
 //! Analyzes a language.
+use crate::{binary, encoding};
+
 use super::{
     matcher::{Filename, FilepathPattern},
-    Category, Language, LANGUAGE_DEFINITIONS,
+    modeline, Category, Language, LANGUAGE_DEFINITIONS,
 };
 
 use indexmap::IndexMap;
 
 use regex::RegexSet;
 use serde::Deserialize;
+use std::collections::HashSet;
 use std::error::Error;
+use std::ffi::OsStr;
 
 use super::matcher::{Extension, Matcher, Shebang};
 use std::path::Path;
 
+/// Common aliases of languages, like in the `gengo-language` attribute.
+/// Aliases are lowercase, and map to the names of built-in languages.
+const ALIASES: &[(&str, &str)] = &[
+    ("bash", "Shell"),
+    ("cpp", "C++"),
+    ("cs", "C#"),
+    ("csharp", "C#"),
+    ("cxx", "C++"),
+    ("dockerfile", "Docker"),
+    ("elisp", "Emacs Lisp"),
+    ("fsharp", "F#"),
+    ("golang", "Go"),
+    ("js", "JavaScript"),
+    ("jsonc", "JSON with Comments"),
+    ("kt", "Kotlin"),
+    ("make", "Makefile"),
+    ("md", "Markdown"),
+    ("node", "JavaScript"),
+    ("objc", "Objective-C"),
+    ("objectivec", "Objective-C"),
+    ("pwsh", "PowerShell"),
+    ("py", "Python"),
+    ("python3", "Python"),
+    ("rb", "Ruby"),
+    ("rs", "Rust"),
+    ("sh", "Shell"),
+    ("text", "Plain Text"),
+    ("ts", "TypeScript"),
+    ("txt", "Plain Text"),
+    ("viml", "Vim Script"),
+    ("yml", "YAML"),
+    ("zsh", "Shell"),
+];
+
 /// Analyzes and attempts to identify a language.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Analyzers(IndexMap<String, Analyzer>);
 
 impl Analyzers {
@@ -28,6 +66,55 @@ impl Analyzers {
         self.0.get(&name).map(|a| &a.language)
     }
 
+    /// Returns a language by name or by a common alias, like `js` or
+    /// `golang`. This is case insensitive, and `-` matches a space, like in
+    /// the `gengo-language` attribute.
+    pub fn resolve(&self, name: &str) -> Option<&Language> {
+        let name = name.to_lowercase();
+        self.get(&name)
+            .or_else(|| self.get(&name.replace('-', " ")))
+            .or_else(|| {
+                ALIASES
+                    .iter()
+                    .find(|(alias, _)| *alias == name)
+                    .and_then(|(_, language)| self.get(language))
+            })
+    }
+
+    /// Returns the language whose name is closest to `name`, for suggesting a
+    /// replacement for a misspelled name. This is case insensitive, and `-`
+    /// matches a space. Returns `None` if no name is close enough.
+    pub fn suggest(&self, name: &str) -> Option<&Language> {
+        let name = name.to_lowercase().replace('-', " ");
+        let max_distance = (name.chars().count() / 3).max(1);
+        self.0
+            .iter()
+            .map(|(key, a)| (edit_distance(&name, key), &a.language))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, language)| language)
+    }
+
+    /// Returns every language, in the order they were defined.
+    pub fn languages(&self) -> impl Iterator<Item = &Language> {
+        self.0.values().map(|a| &a.language)
+    }
+
+    /// Returns the extensions of a language, without the leading `.`. This
+    /// is case insensitive, and is empty if the language doesn't exist.
+    pub fn extensions(&self, name: &str) -> impl Iterator<Item = &OsStr> {
+        let name = name.to_lowercase();
+        self.0
+            .get(&name)
+            .into_iter()
+            .flat_map(|a| a.matchers.iter())
+            .filter_map(|matcher| match matcher {
+                Matcher::Extension(extension) => Some(extension.iter()),
+                _ => None,
+            })
+            .flatten()
+    }
+
     /// Returns the analyzers that have matched by filepath.
     #[deprecated(since = "0.3.1")]
     pub fn by_filepath<P: AsRef<Path>>(&self, filepath: P) -> Found {
@@ -88,7 +175,9 @@ impl Analyzers {
         matches.into()
     }
 
-    /// Returns the analyzers that have matched by filepath pattern.
+    /// Returns the analyzers that have matched by filepath pattern. The
+    /// patterns are matched against the whole filepath, not only the
+    /// filename, so they can depend on the directories a file is in.
     pub fn by_filepath_pattern<P: AsRef<Path>>(&self, filepath: P) -> Found {
         let matches: Vec<_> = self
             .iter()
@@ -130,31 +219,75 @@ impl Analyzers {
         matches.into()
     }
 
+    /// Returns the analyzer declared by an Emacs (`-*- mode: rust -*-`) or
+    /// Vim (`vim: set ft=rust:`) modeline in the first or last 5 lines of
+    /// the contents. Unknown modes are ignored.
+    ///
+    /// The mode can either be the name of a language or one of its extensions.
+    pub fn by_modeline(&self, contents: &[u8]) -> Found {
+        self.by_modeline_text(&encoding::decode(contents))
+    }
+
+    fn by_modeline_text(&self, text: &str) -> Found {
+        let Some(mode) = modeline::find(text) else {
+            return Found::None;
+        };
+        if self.0.contains_key(&mode) {
+            return Found::One(mode);
+        }
+        let filename = format!("modeline.{}", mode);
+        self.by_extension(filename)
+            .first()
+            .map_or(Found::None, |key| Found::One(key.to_owned()))
+    }
+
     /// First pass over a file to determine the language.
     ///
     /// It attempts to identify the file in this order:
     /// 1. by shebang (`#!`)
     /// 2. by filepath
     pub fn simple<P: AsRef<Path>>(&self, filepath: P, contents: &[u8]) -> Found {
+        self.simple_with_detection(filepath, contents).0
+    }
+
+    fn simple_with_detection<P: AsRef<Path>>(
+        &self,
+        filepath: P,
+        contents: &[u8],
+    ) -> (Found, Detection) {
         let matches = self.by_shebang(contents);
         if !matches.is_empty() {
-            return matches;
+            return (matches, Detection::Shebang);
         }
+        self.by_path(filepath)
+    }
+
+    /// Determines the language without reading the contents.
+    ///
+    /// It attempts to identify the file in this order:
+    /// 1. by filename
+    /// 2. by filepath pattern
+    /// 3. by extension
+    fn by_path<P: AsRef<Path>>(&self, filepath: P) -> (Found, Detection) {
         let matches = self.by_filename(&filepath);
         if !matches.is_empty() {
-            return matches;
+            return (matches, Detection::Filename);
         }
         let matches = self.by_filepath_pattern(&filepath);
         if !matches.is_empty() {
-            return matches;
+            return (matches, Detection::Filename);
         }
-        self.by_extension(&filepath)
+        (self.by_extension(&filepath), Detection::Extension)
     }
 
     /// Second pass over a file to determine the language.
     ///
-    /// If a single language isn't found, narrows down the matches by heuristics.
-    /// If none of the found heuristics match, returns the original matches.
+    /// The contents are decoded to text with a detected encoding.
+    ///
+    /// If a single language isn't found, checks for a modeline declaring the
+    /// language. If there is no modeline, narrows down the matches to the
+    /// languages with the most matching heuristics. If none of the found
+    /// heuristics match, returns the original matches.
     ///
     /// Use `limit` to limit the number of bytes to read to match to heuristics.
     pub fn with_heuristics<P: AsRef<Path>>(
@@ -163,34 +296,54 @@ impl Analyzers {
         contents: &[u8],
         limit: usize,
     ) -> Found {
+        self.with_heuristics_and_detection(filepath, contents, limit)
+            .0
+    }
+
+    fn with_heuristics_and_detection<P: AsRef<Path>>(
+        &self,
+        filepath: P,
+        contents: &[u8],
+        limit: usize,
+    ) -> (Found, Detection) {
         let contents = if contents.len() > limit {
             &contents[..limit]
         } else {
             contents
         };
-        let matches = self.simple(filepath, contents);
+        let (matches, detection) = self.simple_with_detection(filepath, contents);
+        if let Found::One(_) = matches {
+            return (matches, detection);
+        }
+        let text = encoding::decode(contents);
+        let modeline_match = self.by_modeline_text(&text);
+        if !modeline_match.is_empty() {
+            return (modeline_match, Detection::Content);
+        }
         let matches = match matches {
-            Found::None | Found::One(_) => return matches,
+            Found::None | Found::One(_) => return (matches, detection),
             Found::Multiple(names) => names,
         };
-        let contents: &str = std::str::from_utf8(contents).unwrap_or_default();
-        let heuristic_matches: Vec<_> = matches
+        // NOTE Each match is scored by how many of its heuristics match, and
+        //      only the matches with the best score are kept.
+        let scores: Vec<_> = matches
             .iter()
             .map(|key| {
                 let a = self.0.get(key).unwrap();
-                (key, a)
+                (key, a.heuristics.matches(&text).iter().count())
             })
-            .filter(|(_, a)| a.heuristics.is_match(contents))
-            .map(|(key, _)| key)
             .collect();
-        if heuristic_matches.is_empty() {
-            return matches.into();
+        let best = scores.iter().map(|(_, score)| *score).max().unwrap_or(0);
+        if best == 0 {
+            return (matches.into(), detection);
         }
-        heuristic_matches
+        let heuristic_matches = scores
             .into_iter()
-            .cloned()
+            .filter(|(_, score)| *score == best)
+            .map(|(key, _)| key.to_owned())
             .collect::<Vec<String>>()
-            .into()
+            .into();
+        (heuristic_matches, Detection::Heuristic)
     }
 
     /// Picks the best language to match to a file.
@@ -199,11 +352,17 @@ impl Analyzers {
     /// matches are attempted by filepath.
     ///
     /// After this, if there are multiple languages, then matches are narrowed
-    /// down using heuristics. `limit` is used here to limit the number of
-    /// bytes to read to match to heuristics.
+    /// down using heuristics, keeping the languages with the most matching
+    /// heuristics. `limit` is used here to limit the number of bytes to read
+    /// to match to heuristics.
     ///
     /// Finally, after this, if there are *still* multiple matching languages,
-    /// then a language is chosen from community-driven priority.
+    /// then a language is chosen from community-driven priority, and then
+    /// by popularity. The priorities of the built-in languages make C the
+    /// default for `.h`, Objective-C the default for `.m`, and TypeScript the
+    /// default for `.ts`.
+    ///
+    /// If the contents are binary, only the filepath is used.
     ///
     /// # Example
     ///
@@ -237,10 +396,41 @@ impl Analyzers {
         contents: &[u8],
         limit: usize,
     ) -> Option<&Language> {
-        let matches = self.with_heuristics(filepath, contents, limit);
+        self.pick_with_detection(filepath, contents, limit)
+            .map(|(language, _)| language)
+    }
+
+    /// Picks the best language to match to a file like `pick`, and also
+    /// returns how the language was detected.
+    pub fn pick_with_detection<P: AsRef<Path>>(
+        &self,
+        filepath: P,
+        contents: &[u8],
+        limit: usize,
+    ) -> Option<(&Language, Detection)> {
+        let (matches, detection) = if binary::is_binary(&contents[..contents.len().min(limit)]) {
+            // NOTE Binary contents can't be used to identify the language.
+            self.by_path(filepath)
+        } else {
+            self.with_heuristics_and_detection(filepath, contents, limit)
+        };
+        self.best(matches).map(|language| (language, detection))
+    }
+
+    /// Picks the best language to match to a file by its shebang alone, like
+    /// for an executable file without an extension.
+    pub fn pick_by_shebang(&self, contents: &[u8]) -> Option<&Language> {
+        self.best(self.by_shebang(contents))
+    }
+
+    /// Picks the matched language with the highest priority, preferring
+    /// popular languages.
+    fn best(&self, matches: Found) -> Option<&Language> {
         let matches = match matches {
             Found::None => return None,
-            Found::One(name) => return self.0.get(&name).map(|a| &a.language),
+            Found::One(name) => {
+                return self.0.get(&name).map(|a| &a.language);
+            }
             Found::Multiple(names) => names,
         };
         let matches = {
@@ -248,11 +438,11 @@ impl Analyzers {
                 .into_iter()
                 .map(|name| self.0.get(&name).unwrap())
                 .collect();
-            matches.sort_by_key(|a| a.priority);
+            matches.sort_by_key(|a| (a.priority, a.language.popular));
             matches.reverse();
             matches
         };
-        matches.get(0).map(|a| &a.language)
+        matches.first().map(|a| &a.language)
     }
 
     /// Creates analyzers from JSON.
@@ -276,6 +466,7 @@ impl Analyzers {
                     name,
                     category: args.category,
                     color: args.color,
+                    popular: args.popular,
                 };
                 let matchers = &args.matchers;
                 let matchers = matchers.into();
@@ -293,6 +484,22 @@ impl Analyzers {
     }
 }
 
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous + usize::from(a != *b);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(previous + 1).min(row[j] + 1);
+        }
+    }
+    row[b.len()]
+}
+
 impl Default for Analyzers {
     /// Create a new language analyzer with default values.
     fn default() -> Self {
@@ -300,6 +507,97 @@ impl Default for Analyzers {
     }
 }
 
+/// Additional languages to merge into `Analyzers`.
+///
+/// On extension and filename collisions, these languages take precedence over
+/// the languages they are merged into.
+///
+/// # Example
+///
+/// ```
+/// use gengo::languages::Category;
+/// use gengo::{Analyzers, LanguageSet};
+///
+/// let languages = LanguageSet::new().language(
+///     "My DSL",
+///     Category::Programming,
+///     "#123456",
+///     &["mydsl"],
+///     &["Dslfile"],
+/// );
+/// let analyzers = languages.merge(Analyzers::default()).unwrap();
+/// let language = analyzers.pick("main.mydsl", b"", 1 << 20).unwrap();
+/// assert_eq!(language.name(), "My DSL");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LanguageSet {
+    languages: Vec<(String, AnalyzerArgs)>,
+}
+
+impl LanguageSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a language that is matched by its extensions and filenames.
+    pub fn language(
+        mut self,
+        name: &str,
+        category: Category,
+        color: &str,
+        extensions: &[&str],
+        filenames: &[&str],
+    ) -> Self {
+        let matchers = AnalyzerArgMatchers {
+            extensions: extensions.iter().map(|s| s.to_string()).collect(),
+            filenames: filenames.iter().map(|s| s.to_string()).collect(),
+            patterns: Vec::new(),
+            interpreters: Vec::new(),
+        };
+        let args = AnalyzerArgs {
+            category,
+            color: color.to_string(),
+            matchers,
+            heuristics: Vec::new(),
+            popular: false,
+            priority: default_priority(),
+        };
+        self.languages.push((name.to_string(), args));
+        self
+    }
+
+    /// Merges the registered languages into `analyzers`.
+    ///
+    /// Returns an error if two registered languages have the same name.
+    pub fn merge(self, mut analyzers: Analyzers) -> Result<Analyzers, Box<dyn Error>> {
+        let mut names = HashSet::with_capacity(self.languages.len());
+        for (name, _) in self.languages.iter() {
+            if !names.insert(name.to_lowercase()) {
+                return Err(format!("language {name:?} is registered more than once").into());
+            }
+        }
+        let languages = Analyzers::from_indexmap(self.languages.into_iter().collect())?;
+        let user_matchers: Vec<_> = languages
+            .0
+            .values()
+            .flat_map(|a| a.matchers.iter())
+            .collect();
+        for analyzer in analyzers.0.values_mut() {
+            for matcher in analyzer.matchers.iter_mut() {
+                for user_matcher in user_matchers.iter() {
+                    match (&mut *matcher, user_matcher) {
+                        (Matcher::Extension(m), Matcher::Extension(other)) => m.remove(other),
+                        (Matcher::Filename(m), Matcher::Filename(other)) => m.remove(other),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        analyzers.0.extend(languages.0);
+        Ok(analyzers)
+    }
+}
+
 /// Used to match a programming language.
 #[derive(Clone, Debug)]
 pub struct Analyzer {
@@ -310,6 +608,25 @@ pub struct Analyzer {
     priority: u8,
 }
 
+/// How the language of a file was detected.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum Detection {
+    /// By the `gengo-language` attribute.
+    Attribute,
+    /// By filename or filepath pattern.
+    Filename,
+    /// By extension.
+    Extension,
+    /// By shebang (`#!`).
+    Shebang,
+    /// By a modeline in the contents.
+    Content,
+    /// By narrowing down multiple matches with heuristics.
+    Heuristic,
+}
+
 /// The result of an analysis. Either multiple results, one result, or no result.
 #[derive(Debug)]
 pub enum Found {
@@ -369,13 +686,15 @@ impl IntoIterator for Found {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 struct AnalyzerArgs {
     category: Category,
     color: String,
     matchers: AnalyzerArgMatchers,
     #[serde(default)]
     heuristics: Vec<String>,
+    #[serde(default)]
+    popular: bool,
     #[serde(default = "default_priority")]
     priority: u8,
 }
@@ -384,7 +703,7 @@ fn default_priority() -> u8 {
     50
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 struct AnalyzerArgMatchers {
     #[serde(default)]
     extensions: Vec<String>,
@@ -0,0 +1,106 @@
+//! Strategies for identifying the language of a file.
+use super::analyzer::{Analyzers, Detection, Found};
+use super::Language;
+use std::path::Path;
+
+/// A strategy for identifying the language of a file with `Analyzers`.
+///
+/// The strategy can be chosen at runtime with `Builder::detector`.
+pub trait Detector: Send + Sync {
+    /// Identifies the language of a file, and how it was identified.
+    ///
+    /// `limit` is the maximum number of bytes of `contents` to read.
+    fn detect<'a>(
+        &self,
+        analyzers: &'a Analyzers,
+        filepath: &Path,
+        contents: &[u8],
+        limit: usize,
+    ) -> Option<(&'a Language, Detection)>;
+
+    /// If the contents of files are needed to identify them. If this is
+    /// `false`, the contents won't be read, and `detect` will be called with
+    /// empty contents.
+    fn reads_contents(&self) -> bool {
+        true
+    }
+}
+
+/// Identifies languages with every available signal, using
+/// `Analyzers::pick_with_detection`.
+///
+/// This is the most accurate strategy, but it's also the slowest, because the
+/// contents of every file may be checked for shebangs, modelines, and
+/// heuristics. This is the default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FullDetector;
+
+impl Detector for FullDetector {
+    fn detect<'a>(
+        &self,
+        analyzers: &'a Analyzers,
+        filepath: &Path,
+        contents: &[u8],
+        limit: usize,
+    ) -> Option<(&'a Language, Detection)> {
+        analyzers.pick_with_detection(filepath, contents, limit)
+    }
+}
+
+/// Identifies languages only by filename, filepath pattern, and extension,
+/// without reading the contents of files.
+///
+/// This is much faster than `FullDetector` for huge repositories, because
+/// only the sizes of files are read. Files that match more than one language
+/// aren't identified. Because the contents aren't read, generated files are
+/// only detected by their paths, and the number of lines is always `0`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExtensionDetector;
+
+impl Detector for ExtensionDetector {
+    fn detect<'a>(
+        &self,
+        analyzers: &'a Analyzers,
+        filepath: &Path,
+        _contents: &[u8],
+        _limit: usize,
+    ) -> Option<(&'a Language, Detection)> {
+        let mut matches = analyzers.by_filename(filepath);
+        let mut detection = Detection::Filename;
+        if matches.is_empty() {
+            matches = analyzers.by_filepath_pattern(filepath);
+        }
+        if matches.is_empty() {
+            matches = analyzers.by_extension(filepath);
+            detection = Detection::Extension;
+        }
+        match matches {
+            Found::One(key) => analyzers.get(&key).map(|language| (language, detection)),
+            Found::None | Found::Multiple(_) => None,
+        }
+    }
+
+    fn reads_contents(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extension_detector() {
+        let analyzers = Analyzers::default();
+        let detect = |filepath: &str| {
+            ExtensionDetector
+                .detect(&analyzers, Path::new(filepath), b"", 0)
+                .map(|(language, detection)| (language.name(), detection))
+        };
+        assert_eq!(detect("main.rs"), Some(("Rust", Detection::Extension)));
+        assert_eq!(detect("Dockerfile"), Some(("Docker", Detection::Filename)));
+        assert_eq!(detect("LICENSE.unknown-extension"), None);
+        assert_eq!(detect("script"), None);
+        assert_eq!(detect("main.h"), None, "C and C++ both match");
+    }
+}
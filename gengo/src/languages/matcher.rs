@@ -35,6 +35,16 @@ impl Extension {
         self.extensions
             .contains(filename.as_ref().extension().unwrap_or_default())
     }
+
+    /// Returns the matched extensions.
+    pub(super) fn iter(&self) -> impl Iterator<Item = &OsStr> {
+        self.extensions.iter().map(OsString::as_os_str)
+    }
+
+    /// Removes the extensions that are also matched by `other`.
+    pub(super) fn remove(&mut self, other: &Self) {
+        self.extensions.retain(|e| !other.extensions.contains(e));
+    }
 }
 
 /// Matches a filename.
@@ -54,6 +64,11 @@ impl Filename {
         self.filenames
             .contains(filename.as_ref().file_name().unwrap_or_default())
     }
+
+    /// Removes the filenames that are also matched by `other`.
+    pub(super) fn remove(&mut self, other: &Self) {
+        self.filenames.retain(|f| !other.filenames.contains(f));
+    }
 }
 
 /// Matches a filepath pattern
@@ -86,7 +101,7 @@ pub struct Shebang {
 }
 
 impl Shebang {
-    const MAX_SHEBANG_LENGTH: usize = 50;
+    const MAX_SHEBANG_LENGTH: usize = 100;
 
     pub fn new<S: Display>(interpreters: &[S]) -> Self {
         let interpreters = interpreters.iter().map(|s| s.to_string()).collect();
@@ -111,13 +126,15 @@ impl Shebang {
         let first_line = String::from_utf8_lossy(first_line);
         // NOTE Handle trailing spaces, `\r`, etc.
         let first_line = first_line.trim_end();
+        // NOTE `env` flags (like `-S`) are skipped, as are the interpreter's arguments.
         static RE: Lazy<Regex> = Lazy::new(|| {
-            Regex::new(r"^#!(?:/usr(?:/local)?)?/bin/(?:env )?([\w\d]+)\r?$").unwrap()
+            Regex::new(r"^#!\s*(?:/usr(?:/local)?)?/bin/(?:env\s+(?:-\S*\s+)*)?([\w\d]+)(?:\s|$)")
+                .unwrap()
         });
 
         RE.captures(first_line)
             .and_then(|c| c.get(1))
-            .map_or(false, |m| {
+            .is_some_and(|m| {
                 let interpreter = m.as_str();
                 self.interpreters.contains(interpreter)
             })
@@ -170,4 +187,27 @@ mod tests {
         assert!(analyzer.matches(b"#!/usr/bin/env python\n"));
         assert!(!analyzer.matches(b"#!/bin/sh\n"));
     }
+
+    #[rstest(
+        contents,
+        case(b"#!/usr/bin/env -S python3 -u\n"),
+        case(b"#!/usr/bin/python3 -u\n"),
+        case(b"#! /usr/bin/python3\n"),
+        case(b"#!/usr/bin/env python3\r\nprint('hello')\r\n")
+    )]
+    fn test_matches_shebang_edge_cases(contents: &[u8]) {
+        let analyzer = Shebang::new(&["python3"]);
+        assert!(analyzer.matches(contents));
+    }
+
+    #[rstest(
+        contents,
+        case(b"#!/usr/bin/python3.11\n"),
+        case(b"#!/usr/bin/env pythonista\n"),
+        case(b"#!/usr/bin/env -S\n")
+    )]
+    fn test_rejects_shebang_edge_cases(contents: &[u8]) {
+        let analyzer = Shebang::new(&["python3"]);
+        assert!(!analyzer.matches(contents));
+    }
 }
@@ -1,22 +1,114 @@
+use analyzer::Analyzers;
+use once_cell::sync::Lazy;
 #[cfg(feature = "owo-colors")]
 use owo_colors::Rgb;
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "owo-colors")]
 use std::error::Error;
 pub mod analyzer;
+pub mod detector;
 mod matcher;
+mod modeline;
 
 const LANGUAGE_DEFINITIONS: &str = include_str!(concat!(env!("OUT_DIR"), "/languages.json"));
 
+/// The built-in languages.
+static BUILT_IN: Lazy<Analyzers> = Lazy::new(Analyzers::default);
+
 /// A programming language.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct Language {
     name: String,
     category: Category,
     color: String,
+    #[serde(default, skip_serializing)]
+    popular: bool,
 }
 
 impl Language {
+    /// Returns every built-in language. Use `Analyzers::extensions` to get
+    /// the extensions of a language.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gengo::Language;
+    ///
+    /// assert!(Language::all().any(|language| language.name() == "Rust"));
+    /// ```
+    pub fn all() -> impl Iterator<Item = &'static Language> {
+        BUILT_IN.languages()
+    }
+
+    /// Returns a built-in language by name or by a common alias. This is
+    /// case insensitive, and `-` matches a space, like in the
+    /// `gengo-language` attribute.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gengo::Language;
+    ///
+    /// assert_eq!(Language::from_name("rust").unwrap().name(), "Rust");
+    /// assert_eq!(Language::from_name("plain-text").unwrap().name(), "Plain Text");
+    /// assert_eq!(Language::from_name("golang").unwrap().name(), "Go");
+    /// assert!(Language::from_name("not a language").is_none());
+    /// ```
+    pub fn from_name(name: &str) -> Option<&'static Language> {
+        BUILT_IN.resolve(name)
+    }
+
+    /// Returns every built-in language that is matched by an extension. The
+    /// leading `.` is optional. Returns `None` if no language is matched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gengo::Language;
+    ///
+    /// let languages = Language::from_extension(".rs").unwrap();
+    /// assert_eq!(languages[0].name(), "Rust");
+    /// assert!(Language::from_extension("not-an-extension").is_none());
+    /// ```
+    pub fn from_extension(extension: &str) -> Option<Vec<&'static Language>> {
+        let extension = extension.strip_prefix('.').unwrap_or(extension);
+        let languages: Vec<_> = BUILT_IN
+            .languages()
+            .filter(|language| BUILT_IN.extensions(language.name()).any(|e| e == extension))
+            .collect();
+        (!languages.is_empty()).then_some(languages)
+    }
+
+    /// The pseudo-language that summaries count unidentified text files as.
+    pub(crate) fn unknown() -> Language {
+        Language {
+            name: "Unknown".to_string(),
+            category: Category::Data,
+            color: "#CCCCCC".to_string(),
+            popular: false,
+        }
+    }
+
+    /// The pseudo-language that summaries count unidentified binary files as.
+    pub(crate) fn binary() -> Language {
+        Language {
+            name: "Binary".to_string(),
+            category: Category::Data,
+            color: "#808080".to_string(),
+            popular: false,
+        }
+    }
+
+    /// The pseudo-language that summaries collapse small languages into.
+    pub(crate) fn other() -> Language {
+        Language {
+            name: "Other".to_string(),
+            category: Category::Data,
+            color: "#EDEDED".to_string(),
+            popular: false,
+        }
+    }
+
     /// Returns the name of the language.
     pub fn name(&self) -> &str {
         &self.name
@@ -27,7 +119,16 @@ impl Language {
         &self.category
     }
 
-    /// Returns the color of the language.
+    /// Returns if the language is popular. When the heuristics can't choose
+    /// between languages with the same priority, popular languages are
+    /// preferred.
+    pub fn is_popular(&self) -> bool {
+        self.popular
+    }
+
+    /// Returns the color of the language as a hex string, like `#DD3515`.
+    /// Every language defines a color, so this can be used to pair
+    /// languages in a summary with their display colors.
     pub fn color(&self) -> &str {
         &self.color
     }
@@ -48,7 +149,7 @@ impl Language {
 }
 
 /// A category for a language.
-#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Category {
     /// Data files. Examples: JSON, YAML, XML, CSV, etc.
@@ -62,3 +163,27 @@ pub enum Category {
     /// Query languages. Examples: SQL, GraphQL, etc.
     Query,
 }
+
+impl Category {
+    /// Returns every category.
+    pub fn all() -> impl Iterator<Item = Category> {
+        [
+            Self::Data,
+            Self::Markup,
+            Self::Programming,
+            Self::Prose,
+            Self::Query,
+        ]
+        .into_iter()
+    }
+
+    /// Checks if files of this category are detectable by default. Files
+    /// that are generated, documentation, or vendored are never detectable
+    /// by default.
+    pub fn is_detectable(&self) -> bool {
+        match self {
+            Self::Data | Self::Prose => false,
+            Self::Programming | Self::Markup | Self::Query => true,
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Finds the language declared by Emacs and Vim modelines.
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// The number of lines at the start and end of a file to check for modelines.
+const SEARCH_LINES: usize = 5;
+
+/// Matches `-*- rust -*-` and `-*- mode: rust -*-`.
+static EMACS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"-\*-(?:\s*([\w+#.-]+)\s*-\*-|.*?\bmode\s*:\s*([\w+#.-]+))").unwrap());
+
+/// Matches `vim: set ft=rust:`, `vi: filetype=rust`, etc.
+static VIM: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:^|\s)(?:vi|vim|ex)(?:[<=>]?\d+)?:.*?\b(?:ft|filetype|syntax)\s*=\s*([\w+#.-]+)")
+        .unwrap()
+});
+
+/// Returns the lowercase mode declared by the first modeline in the first or
+/// last few lines of the contents.
+pub(super) fn find(contents: &str) -> Option<String> {
+    let lines: Vec<_> = contents.lines().collect();
+    let head = lines.iter().take(SEARCH_LINES);
+    let tail = lines
+        .iter()
+        .skip(SEARCH_LINES.max(lines.len().saturating_sub(SEARCH_LINES)));
+    head.chain(tail)
+        .find_map(|line| parse(line))
+        .map(|mode| mode.to_lowercase())
+}
+
+/// Parses the mode from a single line.
+fn parse(line: &str) -> Option<&str> {
+    EMACS
+        .captures(line)
+        .and_then(|c| c.get(1).or_else(|| c.get(2)))
+        .or_else(|| VIM.captures(line).and_then(|c| c.get(1)))
+        .map(|m| m.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        line,
+        expected,
+        case("// -*- rust -*-", Some("rust")),
+        case("# -*- mode: python -*-", Some("python")),
+        case("# -*- coding: utf-8; mode: ruby -*-", Some("ruby")),
+        case("# -*- coding: utf-8 -*-", None),
+        case("// vim: set ft=javascript:", Some("javascript")),
+        case("/* vim: filetype=c */", Some("c")),
+        case("# vi: syntax=sh", Some("sh")),
+        case("// ex: ts=4", None),
+        case("let vim = 1;", None)
+    )]
+    fn test_parse(line: &str, expected: Option<&str>) {
+        assert_eq!(parse(line), expected);
+    }
+
+    #[test]
+    fn test_find_first_and_last_lines() {
+        let mut contents = String::from("line\n").repeat(20);
+        contents.push_str("// vim: ft=rust\n");
+        assert_eq!(find(&contents), Some("rust".into()));
+
+        let contents = format!("// vim: ft=rust\n{}", "line\n".repeat(20));
+        assert_eq!(find(&contents), Some("rust".into()));
+
+        let contents = format!(
+            "line\n{}// vim: ft=rust\n{}",
+            "line\n".repeat(10),
+            "line\n".repeat(10)
+        );
+        assert_eq!(find(&contents), None);
+    }
+
+    #[test]
+    fn test_find_prefers_first() {
+        let contents = "# -*- mode: python -*-\n# vim: ft=ruby\n";
+        assert_eq!(find(contents), Some("python".into()));
+    }
+}
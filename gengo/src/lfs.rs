@@ -0,0 +1,107 @@
+//! Git LFS pointer files.
+//!
+//! Git LFS stores a small pointer file in the tree instead of the contents of
+//! a file. Pointers aren't analyzed as text; they are identified by their path,
+//! and have the size of the file they point to, unless a `BlobReader` returns
+//! the real contents.
+
+/// The first line of every pointer file.
+const VERSION: &str = "version https://git-lfs.github.com/spec/v1";
+
+/// Pointer files are always smaller than this.
+const MAX_SIZE: usize = 1024;
+
+/// A parsed Git LFS pointer file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pointer {
+    oid: String,
+    size: usize,
+}
+
+impl Pointer {
+    /// Parses the contents of a blob as a pointer file. Returns `None` if the
+    /// contents aren't a pointer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gengo::lfs::Pointer;
+    ///
+    /// let contents = b"version https://git-lfs.github.com/spec/v1\n\
+    ///                  oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\n\
+    ///                  size 12345\n";
+    /// let pointer = Pointer::parse(contents).unwrap();
+    /// assert_eq!(pointer.size(), 12345);
+    /// assert!(Pointer::parse(b"fn main() {}\n").is_none());
+    /// ```
+    pub fn parse(contents: &[u8]) -> Option<Self> {
+        if contents.len() >= MAX_SIZE {
+            return None;
+        }
+        let text = std::str::from_utf8(contents).ok()?;
+        let mut lines = text.lines();
+        if lines.next()? != VERSION {
+            return None;
+        }
+        let mut oid = None;
+        let mut size = None;
+        for line in lines {
+            let (key, value) = line.split_once(' ')?;
+            match key {
+                "oid" => oid = Some(value.to_string()),
+                "size" => size = Some(value.parse().ok()?),
+                _ => {}
+            }
+        }
+        Some(Self {
+            oid: oid?,
+            size: size?,
+        })
+    }
+
+    /// The id of the object that the pointer points to, including the hash
+    /// algorithm, like `sha256:4d7a...`.
+    pub fn oid(&self) -> &str {
+        &self.oid
+    }
+
+    /// The size of the file that the pointer points to.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        contents,
+        expected,
+        case(
+            "version https://git-lfs.github.com/spec/v1\noid sha256:abc\nsize 12\n",
+            Some(12)
+        ),
+        case(
+            "version https://git-lfs.github.com/spec/v1\r\noid sha256:abc\r\nsize 12\r\n",
+            Some(12)
+        ),
+        case(
+            "version https://git-lfs.github.com/spec/v1\next-0-foo sha256:def\noid sha256:abc\nsize 12\n",
+            Some(12)
+        ),
+        case("version https://git-lfs.github.com/spec/v1\noid sha256:abc\n", None),
+        case("version https://git-lfs.github.com/spec/v1\nsize 12\n", None),
+        case(
+            "version https://git-lfs.github.com/spec/v1\noid sha256:abc\nsize many\n",
+            None
+        ),
+        case("version 2\noid sha256:abc\nsize 12\n", None),
+        case("", None)
+    )]
+    fn test_parse(contents: &str, expected: Option<usize>) {
+        let pointer = Pointer::parse(contents.as_bytes());
+        assert_eq!(pointer.map(|pointer| pointer.size()), expected);
+    }
+}
@@ -7,32 +7,114 @@
 #![doc = include_str!(concat!(env!("OUT_DIR"), "/language-list.md"))]
 
 pub use analysis::Analysis;
+use analysis::SkipReason;
+#[cfg(feature = "git")]
+use analysis::Stream;
+#[cfg(feature = "git")]
+use analysis::Summary;
+pub use attributes::{Assignment, AttributeState};
+#[cfg(feature = "git")]
+use attributes::{Overrides, ATTRIBUTE_COUNT, ATTRIBUTE_NAMES};
+#[cfg(feature = "git")]
+pub use blame::Author;
+#[cfg(feature = "git")]
+use bstr::BString;
+use bstr::{BStr, ByteSlice};
 pub use builder::Builder;
+#[cfg(feature = "git")]
+use builder::Configured;
+use builder::Settings;
+pub use cache::BlobCache;
+use cache::CachedBlob;
+#[cfg(feature = "git")]
+use config::Config;
 use documentation::Documentation;
 pub use error::{Error, ErrorKind};
+pub use explanation::Explanation;
+use file_source::FileSource;
 use generated::Generated;
+#[cfg(feature = "git")]
 use gix::attrs::StateRef;
-use gix::bstr::ByteSlice;
+#[cfg(feature = "git")]
 use gix::prelude::FindExt;
 use glob::MatchOptions;
-pub use languages::analyzer::Analyzers;
+use indexmap::IndexMap;
+pub use languages::analyzer::{Analyzers, Detection, LanguageSet};
+pub use languages::detector::{Detector, ExtensionDetector, FullDetector};
 use languages::Category;
 pub use languages::Language;
+pub use reader::BlobReader;
 
+#[cfg(feature = "git")]
+use std::borrow::Cow;
+#[cfg(feature = "git")]
+use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
 use std::path::Path;
-use std::sync::atomic::Ordering;
+#[cfg(feature = "git")]
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+#[cfg(feature = "git")]
+use std::sync::Mutex;
 use vendored::Vendored;
 
 pub mod analysis;
+mod attributes;
+mod binary;
+#[cfg(feature = "git")]
+mod blame;
 mod builder;
+mod cache;
+mod config;
 mod documentation;
+mod embedded;
+mod encoding;
 mod error;
+mod explanation;
+pub mod file_source;
 mod generated;
 pub mod languages;
+pub mod lfs;
+mod reader;
+pub mod report;
 mod vendored;
 
 type Result<T, E = Box<dyn std::error::Error + Send + Sync + 'static>> = std::result::Result<T, E>;
 
+/// Analyzes files held in memory with the default settings, like files that
+/// were uploaded to a web page. Neither the filesystem nor a repository is
+/// used, so git attributes don't apply. This needs the `wasm` feature, and
+/// doesn't need any other features, so it can be built for targets like
+/// `wasm32-unknown-unknown`.
+///
+/// Use `Builder::build_without_repository` and `Gengo::analyze_source` to
+/// change the settings.
+///
+/// # Example
+///
+/// ```
+/// let files = [("src/main.rs".to_string(), b"fn main() {}\n".to_vec())];
+/// let analysis = gengo::analyze_files(&files);
+/// assert_eq!(analysis.get("src/main.rs").unwrap().language().name(), "Rust");
+/// ```
+#[cfg(feature = "wasm")]
+pub fn analyze_files(files: &[(String, Vec<u8>)]) -> Analysis {
+    let gengo = Builder::new("")
+        .threads(1)
+        .build_without_repository()
+        .expect("default settings should be valid");
+    let memory = file_source::Memory::new(
+        files
+            .iter()
+            .map(|(path, contents)| (Path::new(path), contents.as_slice())),
+    );
+    gengo
+        .analyze_source(&memory)
+        .expect("files in memory should be readable")
+}
+
 /// Shared match options for consistent behavior.
 const GLOB_MATCH_OPTIONS: MatchOptions = MatchOptions {
     case_sensitive: true,
@@ -41,91 +123,1187 @@ const GLOB_MATCH_OPTIONS: MatchOptions = MatchOptions {
 };
 
 /// The main entry point for Gengo.
+#[derive(Clone)]
+// NOTE Without the git feature, the settings for repositories are unused.
+#[cfg_attr(not(feature = "git"), allow(dead_code))]
 pub struct Gengo {
-    repository: gix::ThreadSafeRepository,
+    /// The repository to analyze, which is `None` if only other sources are
+    /// analyzed.
+    #[cfg(feature = "git")]
+    repository: Option<gix::ThreadSafeRepository>,
     analyzers: Analyzers,
+    detector: Arc<dyn Detector>,
     read_limit: usize,
+    threads: usize,
     documentation: Documentation,
     generated: Generated,
     vendored: Vendored,
+    #[cfg(feature = "git")]
+    overrides: Overrides,
+    cache: Option<Arc<BlobCache>>,
+    submodules: SubmodulePolicy,
+    max_submodule_depth: usize,
+    detectable_categories: HashSet<Category>,
+    exclude: Vec<glob::Pattern>,
+    only_extensions: Option<HashSet<String>>,
+    blob_reader: Option<Arc<dyn BlobReader>>,
+    max_blob_size: Option<usize>,
+    category_overrides: HashMap<Language, Category>,
+    untracked: bool,
+    embedded_languages: bool,
+    /// The settings of the builder, if `.gengo.toml` files are read.
+    settings: Option<Arc<Settings>>,
+    /// The id of the `.gengo.toml` file that this instance was configured
+    /// with, so that blobs that were analyzed with other settings aren't
+    /// reused from the cache.
+    config_id: Option<gix_hash::ObjectId>,
 }
 
+#[cfg(feature = "git")]
 #[derive(Clone)]
 struct GitState {
     attr_stack: gix::worktree::Stack,
     attr_matches: gix::attrs::search::Outcome,
+    override_matches: gix::attrs::search::Outcome,
 }
 
+#[cfg(feature = "git")]
 impl GitState {
-    fn new(repo: &gix::Repository, tree_id: &gix::oid) -> Result<(Self, gix::index::State)> {
-        let index = repo.index_from_tree(tree_id)?;
-        let attr_stack = repo.attributes_only(
-            &index,
+    fn new(
+        repo: &gix::Repository,
+        tree_id: &gix::oid,
+        overrides: &Overrides,
+    ) -> Result<(Self, gix::index::State)> {
+        let index = index_from_tree(repo, tree_id)?;
+        // NOTE Attributes are read from the tree's blobs, never the worktree,
+        //      so bare repositories are supported.
+        Self::from_index(
+            repo,
+            index,
             gix::worktree::stack::state::attributes::Source::IdMapping,
-        )?;
-        let attr_matches = attr_stack.selected_attribute_matches([
-            "gengo-language",
-            "gengo-generated",
-            "gengo-documentation",
-            "gengo-vendored",
-            "gengo-detectable",
-        ]);
+            overrides,
+        )
+    }
+
+    /// Reads attributes for the files of an index from `source`.
+    fn from_index(
+        repo: &gix::Repository,
+        index: gix::index::State,
+        source: gix::worktree::stack::state::attributes::Source,
+        overrides: &Overrides,
+    ) -> Result<(Self, gix::index::State)> {
+        let attr_stack = repo.attributes_only(&index, source)?;
+        let attr_matches = attr_stack.selected_attribute_matches(ATTRIBUTE_NAMES);
         Ok((
             Self {
                 attr_stack: attr_stack.detach(),
                 attr_matches,
+                override_matches: overrides.outcome(),
             },
-            index.into_parts().0,
+            index,
         ))
     }
+
+    /// Matches the gengo attributes of a file, in the order of
+    /// `ATTRIBUTE_NAMES`, followed by the `text` attribute. Attributes that
+    /// aren't specified are `None`.
+    ///
+    /// The Linguist equivalent of an attribute is used if the attribute
+    /// isn't specified.
+    fn attributes(
+        &mut self,
+        filepath: &Path,
+        repo: &gix::Repository,
+        overrides: &Overrides,
+    ) -> Result<[Option<gix::attrs::search::Match<'_>>; ATTRIBUTE_COUNT + 1]> {
+        self.attr_stack
+            .at_path(filepath, Some(false), |id, buf| {
+                repo.objects.find_blob(id, buf)
+            })?
+            .matching_attributes(&mut self.attr_matches);
+        overrides.matching_attributes(filepath, &mut self.override_matches);
+
+        let mut attrs = [None, None, None, None, None, None];
+        // NOTE The attributes file takes precedence over the repository's attributes.
+        for matches in [&self.attr_matches, &self.override_matches] {
+            let mut gengo: Vec<_> = matches.iter_selected().collect();
+            if let Some(text) = gengo
+                .pop()
+                .filter(|info| info.assignment.state != StateRef::Unspecified)
+            {
+                attrs[ATTRIBUTE_COUNT] = Some(text);
+            }
+            let linguist = gengo.split_off(ATTRIBUTE_COUNT);
+            // NOTE gengo attributes take precedence over linguist attributes.
+            for infos in [linguist, gengo] {
+                infos
+                    .into_iter()
+                    .zip(attrs.iter_mut())
+                    .filter(|(info, _)| info.assignment.state != StateRef::Unspecified)
+                    .for_each(|(info, slot)| *slot = Some(info));
+            }
+        }
+        Ok(attrs)
+    }
 }
 
+#[derive(Clone)]
 struct BlobEntry {
-    // Just for path and id access
-    index_entry: gix::index::Entry,
+    /// The range of the path in the path storage.
+    path: Range<usize>,
+    /// The id of the blob.
+    id: gix_hash::ObjectId,
     result: Option<Entry>,
+    /// The value of a `gengo-language` attribute that isn't a known
+    /// language, and the closest known language.
+    unresolved_language: Option<(String, Option<String>)>,
+    /// Why the blob wasn't analyzed, if it was skipped.
+    skipped: Option<SkipReason>,
+    /// The blob, if it was analyzed but its language wasn't identified.
+    unidentified: Option<Unidentified>,
+    /// If the file was modified in the worktree, so it is read from the
+    /// worktree instead of the object database. `id` is then the id of its
+    /// contents in the worktree.
+    modified: bool,
+    /// If the file has the executable bit set.
+    executable: bool,
+    /// If the file isn't in the index, so it is read from the worktree.
+    untracked: bool,
+}
+
+/// A blob whose language wasn't identified.
+#[derive(Clone, Copy, Debug)]
+struct Unidentified {
+    size: usize,
+    lines: usize,
+    chars: usize,
+    binary: bool,
+    /// If the blob isn't generated, documentation, or vendored, or if the
+    /// `gengo-detectable` attribute is set.
+    detectable: bool,
+}
+
+/// How the contents of a blob were read.
+#[derive(Clone, Copy, Debug)]
+struct BlobRead {
+    /// The full size of the blob.
+    size: usize,
+    /// If the blob is already known to be binary.
+    binary: bool,
+    /// If only the start of the blob was read, so its lines and characters
+    /// aren't counted.
+    partial: bool,
+    /// If the contents were read, instead of only the size.
+    reads_contents: bool,
+    /// If the file has the executable bit set.
+    executable: bool,
 }
 
 /// The result of analyzing a repository or a single submodule
+#[derive(Clone, Default)]
 struct Results {
     entries: Vec<BlobEntry>,
-    path_storage: gix::index::PathStorage,
+    path_storage: Vec<u8>,
 }
 
 impl Results {
     /// Create a data structure that holds index entries as well as our results per entry.
-    fn from_index(index: gix::index::State) -> Self {
+    ///
+    /// Only the entries under `dir` are kept. An empty `dir` keeps all entries.
+    /// Entries whose paths are `excluded` are left out. Symlinks are kept, but
+    /// are already skipped.
+    #[cfg(feature = "git")]
+    fn from_index<F>(index: gix::index::State, dir: &BStr, excluded: F) -> Self
+    where
+        F: Fn(&BStr) -> bool,
+    {
         use gix::index::entry::Mode;
 
-        let (entries, path_storage) = index.into_entries();
-        let entries: Vec<_> = entries
-            .into_iter()
-            .filter(|e| matches!(e.mode, Mode::FILE | Mode::FILE_EXECUTABLE))
-            .map(|e| BlobEntry {
-                index_entry: e,
-                result: None,
-            })
-            .collect();
+        let mut results = Results::default();
+        for entry in index
+            .entries()
+            .iter()
+            .filter(|e| matches!(e.mode, Mode::FILE | Mode::FILE_EXECUTABLE | Mode::SYMLINK))
+            .filter(|e| is_under(e.path(&index), dir))
+            .filter(|e| !excluded(e.path(&index)))
+        {
+            results.push(entry.path(&index), entry.id, None);
+            let Some(last) = results.entries.last_mut() else {
+                continue;
+            };
+            last.executable = entry.mode == Mode::FILE_EXECUTABLE;
+            // NOTE Symlinks are kept so that they are reported, but their
+            //      targets aren't analyzed.
+            if entry.mode == Mode::SYMLINK {
+                last.skipped = Some(SkipReason::Symlink);
+            }
+        }
+        results
+    }
 
-        Results {
-            entries,
-            path_storage,
+    /// Adds an entry, copying its path into the path storage.
+    fn push(&mut self, path: &BStr, id: gix_hash::ObjectId, result: Option<Entry>) {
+        let start = self.path_storage.len();
+        self.path_storage.extend_from_slice(path);
+        self.entries.push(BlobEntry {
+            path: start..self.path_storage.len(),
+            id,
+            result,
+            unresolved_language: None,
+            skipped: None,
+            unidentified: None,
+            modified: false,
+            executable: false,
+            untracked: false,
+        });
+    }
+
+    /// Moves the entries of `other` into these results, prefixing their
+    /// paths with `root`.
+    fn extend_under(&mut self, root: &BStr, other: Results) {
+        for entry in other.entries {
+            let start = self.path_storage.len();
+            self.path_storage.extend_from_slice(root);
+            self.path_storage
+                .extend_from_slice(&other.path_storage[entry.path]);
+            self.entries.push(BlobEntry {
+                path: start..self.path_storage.len(),
+                id: entry.id,
+                result: entry.result,
+                unresolved_language: entry.unresolved_language,
+                skipped: entry.skipped,
+                unidentified: entry.unidentified,
+                modified: entry.modified,
+                executable: entry.executable,
+                untracked: entry.untracked,
+            });
+        }
+    }
+
+    /// Gets the path of an entry.
+    fn path(&self, entry: &BlobEntry) -> &BStr {
+        self.path_storage[entry.path.clone()].as_bstr()
+    }
+}
+
+/// Resolves a revision, like a branch, tag, or abbreviated commit, to the id
+/// of its tree.
+#[cfg(feature = "git")]
+fn tree_id(repo: &gix::Repository, rev: &str) -> Result<gix::ObjectId> {
+    resolve(repo, rev).map(|(tree_id, _)| tree_id)
+}
+
+/// Resolves a revision to the id of its tree, and the id of its commit. The
+/// commit is `None` if the revision is a tree.
+///
+/// In a repository without commits, `HEAD` and the branch it points to are
+/// resolved to the empty tree.
+#[cfg(feature = "git")]
+fn resolve(repo: &gix::Repository, rev: &str) -> Result<(gix::ObjectId, Option<gix::ObjectId>)> {
+    fn bad_revision<E>(rev: &str) -> impl FnOnce(E) -> Error + '_
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        move |err| Error::with_source(ErrorKind::BadRevision, err).with_context(rev)
+    }
+    let id = match repo.rev_parse_single(rev) {
+        Ok(id) => id,
+        Err(err) => {
+            let head = repo.head().map_err(bad_revision(rev))?;
+            if !head.is_unborn() {
+                return Err(Box::new(bad_revision(rev)(err)));
+            }
+            let is_head = rev == "HEAD"
+                || repo.head_name()?.is_some_and(|name| {
+                    let name = name.as_ref();
+                    name.as_bstr() == rev || name.shorten() == rev
+                });
+            if is_head {
+                return Ok((gix::ObjectId::empty_tree(repo.object_hash()), None));
+            }
+            return Err(Box::new(
+                Error::with_source(ErrorKind::NoCommits, err).with_context(rev),
+            ));
+        }
+    };
+    let commit_id = id
+        .object()
+        .ok()
+        .and_then(|object| object.peel_to_kind(gix::object::Kind::Commit).ok())
+        .map(|commit| commit.id);
+    let tree = id
+        .object()
+        .map_err(bad_revision(rev))?
+        .peel_to_tree()
+        .map_err(bad_revision(rev))?;
+    Ok((tree.id, commit_id))
+}
+
+/// Discovers the repository containing `path`.
+#[cfg(feature = "git")]
+fn discover<P: AsRef<Path>>(path: P) -> std::result::Result<gix::Repository, Error> {
+    let path = path.as_ref();
+    gix::discover(path).map_err(|err| {
+        let err = match err {
+            gix::discover::Error::Discover(err) => Error::with_source(ErrorKind::NoRepository, err),
+            err => Error::with_source(ErrorKind::Discovery, err),
+        };
+        err.with_context(path.display().to_string())
+    })
+}
+
+/// Reads a tree into an index.
+#[cfg(feature = "git")]
+fn index_from_tree(repo: &gix::Repository, tree_id: &gix::oid) -> Result<gix::index::State> {
+    // NOTE The empty tree isn't stored in repositories without commits.
+    if tree_id == gix::ObjectId::empty_tree(repo.object_hash()) {
+        return Ok(gix::index::State::new(repo.object_hash()));
+    }
+    Ok(repo.index_from_tree(tree_id)?.into_parts().0)
+}
+
+/// Checks if `path` is `dir` or is inside of `dir`. Every path is under an
+/// empty `dir`.
+#[cfg(feature = "git")]
+fn is_under(path: &BStr, dir: &BStr) -> bool {
+    dir.is_empty()
+        || path
+            .strip_prefix(dir.as_bytes())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(b"/"))
+}
+
+/// How submodules are analyzed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum SubmodulePolicy {
+    /// Analyzes the files of submodules like any other files.
+    Recurse,
+    /// Analyzes the files of submodules, and marks them as vendored unless
+    /// the `gengo-vendored` attribute says otherwise.
+    #[default]
+    RecurseAsVendored,
+    /// Skips submodules entirely.
+    Ignore,
+}
+
+/// A submodule that should be analyzed.
+#[cfg(feature = "git")]
+struct Submodule {
+    /// The path of the submodule relative to the root repository, with a trailing `/`.
+    root: BString,
+    /// The directory in the submodule to analyze. Empty for the whole submodule.
+    dir: BString,
+    repo: gix::Repository,
+    tree_id: gix::ObjectId,
+    /// The commit that the submodule is pinned to. For the root repository,
+    /// this is `None` if the revision was resolved to a tree without a commit.
+    commit_id: Option<gix::ObjectId>,
+    /// How deeply the submodule is nested. The root repository is `0`.
+    depth: usize,
+}
+
+/// A repository or submodule whose files are ready to be analyzed.
+#[cfg(feature = "git")]
+struct PreparedSubmodule {
+    /// The path of the submodule relative to the root repository, with a trailing `/`.
+    root: BString,
+    repo: gix::Repository,
+    state: GitState,
+    /// The files to analyze, relative to the root of the submodule.
+    results: Results,
+    /// If the files should be marked as vendored.
+    vendored: bool,
+}
+
+#[cfg(feature = "git")]
+impl Submodule {
+    /// Identifies submodules that have the same results when analyzed.
+    fn key(&self) -> (gix::ObjectId, BString) {
+        (self.tree_id, self.dir.clone())
+    }
+}
+
+/// Called with the number of processed files and the total number of files.
+#[cfg(feature = "git")]
+type ProgressCallback<'a> = &'a mut (dyn FnMut(usize, usize) + Send);
+
+/// Lets callers observe and control an analysis while it runs.
+#[cfg(feature = "git")]
+struct Hooks<'a> {
+    interrupt: &'a AtomicBool,
+    /// The number of processed files, the total number of files, and the
+    /// callback to report them to.
+    progress: Mutex<(usize, usize, ProgressCallback<'a>)>,
+}
+
+#[cfg(feature = "git")]
+impl<'a> Hooks<'a> {
+    fn new(interrupt: &'a AtomicBool, progress: ProgressCallback<'a>) -> Self {
+        Self {
+            interrupt,
+            progress: Mutex::new((0, 0, progress)),
         }
     }
+
+    fn is_interrupted(&self) -> bool {
+        self.interrupt.load(Ordering::Relaxed)
+    }
+
+    /// Adds files that need to be processed.
+    fn add_total(&self, n: usize) {
+        let mut progress = self.progress.lock().unwrap();
+        progress.1 += n;
+    }
+
+    /// Marks a file as processed.
+    fn increment(&self) {
+        let mut progress = self.progress.lock().unwrap();
+        progress.0 += 1;
+        let (processed, total, callback) = &mut *progress;
+        callback(*processed, *total);
+    }
 }
 
+#[cfg(feature = "git")]
 impl Gengo {
     /// Analyzes each file in the repository at the given revision.
+    ///
+    /// Submodules that have been checked out are also analyzed, and their
+    /// files are considered vendored.
+    ///
+    /// If the repository was opened from a linked worktree, like one created
+    /// with `git worktree add`, `HEAD` is the head of that worktree.
     pub fn analyze(&self, rev: &str) -> Result<Analysis> {
-        let repo = self.repository.to_thread_local();
-        let tree_id = repo.rev_parse_single(rev)?.object()?.peel_to_tree()?.id;
+        self.analyze_with_interrupt(rev, &AtomicBool::new(false))
+    }
+
+    /// Analyzes several revisions, like the history of a branch, returning
+    /// each revision with its analysis in the same order. Returns the first
+    /// error if any revision can't be analyzed.
+    ///
+    /// The repository is only opened once. If a `BlobCache` was given to the
+    /// builder, the blobs that don't change between revisions are only read
+    /// once.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use gengo::{BlobCache, Builder};
+    /// use std::sync::Arc;
+    ///
+    /// let gengo = Builder::new("path/to/repo")
+    ///     .cache(Arc::new(BlobCache::new()))
+    ///     .build()
+    ///     .unwrap();
+    /// for (rev, analysis) in gengo.analyze_revisions(&["HEAD~2", "HEAD~1", "HEAD"]).unwrap() {
+    ///     println!("{rev}: {} files", analysis.len());
+    /// }
+    /// ```
+    pub fn analyze_revisions(&self, revs: &[&str]) -> Result<Vec<(String, Analysis)>> {
+        revs.iter()
+            .map(|rev| Ok((rev.to_string(), self.analyze(rev)?)))
+            .collect()
+    }
+
+    /// Counts the lines of each language that each author last changed, by
+    /// blaming the detectable files at the given revision. Generated and
+    /// vendored files, and the files of submodules, are left out.
+    ///
+    /// This reads every version of every file, so it is much slower than
+    /// `analyze`. When `interrupt` is set to `true`, it stops early, and the
+    /// lines that weren't blamed yet aren't counted.
+    ///
+    /// Returns an error if the revision isn't a commit.
+    pub fn blame(&self, rev: &str, interrupt: &AtomicBool) -> Result<HashMap<Author, Summary>> {
+        let repo = self.repository()?;
+        let Some(commit_id) = resolve(&repo, rev)?.1 else {
+            return Err(Box::new(
+                Error::new(ErrorKind::BadRevision).with_context(rev),
+            ));
+        };
+        let files = self
+            .analyze_with_interrupt(rev, interrupt)?
+            .iter()
+            .filter(|(_, entry)| entry.detectable() && !(entry.generated() || entry.vendored()))
+            .map(|(path, entry)| (path.into_owned(), entry.language().clone()))
+            .collect();
+        blame::blame(&repo, commit_id, files, interrupt)
+    }
+
+    /// Analyzes each file in the repository at the given revision, stopping
+    /// early when `interrupt` is set to `true`.
+    ///
+    /// This allows a long-running analysis to be cancelled from another
+    /// thread. When interrupted, the returned analysis contains the results
+    /// for the files that were analyzed before the interruption.
+    pub fn analyze_with_interrupt(&self, rev: &str, interrupt: &AtomicBool) -> Result<Analysis> {
+        self.analyze_with_progress(rev, interrupt, |_, _| {})
+    }
+
+    /// Analyzes each file in the repository at the given revision, calling
+    /// `progress` with the number of processed files and the total number of
+    /// files each time a file is analyzed. Analysis stops early when
+    /// `interrupt` is set to `true`.
+    ///
+    /// Calls to `progress` are synchronized, so the processed count is always
+    /// increasing. The total can grow as submodules are discovered.
+    pub fn analyze_with_progress<F>(
+        &self,
+        rev: &str,
+        interrupt: &AtomicBool,
+        mut progress: F,
+    ) -> Result<Analysis>
+    where
+        F: FnMut(usize, usize) + Send,
+    {
+        let hooks = Hooks::new(interrupt, &mut progress);
+        self.analyze_dir(rev, BString::default(), &hooks)
+    }
+
+    /// Analyzes the repository at the given revision, yielding each entry as
+    /// its file is analyzed. Unlike `analyze`, the entries aren't kept in
+    /// memory, so they can be aggregated and discarded as they arrive.
+    ///
+    /// Returns an error if the revision can't be resolved.
+    pub fn analyze_streaming(&self, rev: &str) -> Result<Stream<'_>> {
+        let repo = self.repository()?;
+        let (tree_id, commit_id) = resolve(&repo, rev)?;
+        let gengo = self.for_tree(&repo, &tree_id)?;
+        let root = Submodule {
+            root: BString::default(),
+            dir: BString::default(),
+            repo,
+            tree_id,
+            commit_id,
+            depth: 0,
+        };
+        Ok(Stream::new(gengo, root))
+    }
+
+    /// Analyzes only the files in a directory of the repository at the given
+    /// revision. The paths in the analysis are still relative to the root of
+    /// the repository.
+    ///
+    /// Submodules in the directory are also analyzed, and the directory can
+    /// be inside of a submodule.
+    pub fn analyze_path<P: AsRef<Path>>(&self, rev: &str, dir: P) -> Result<Analysis> {
+        let dir = gix::path::into_bstr(dir.as_ref());
+        let dir = dir.trim_end_with(|c| c == '/').as_bstr().to_owned();
+        let interrupt = AtomicBool::new(false);
+        let mut progress = |_, _| {};
+        let hooks = Hooks::new(&interrupt, &mut progress);
+        self.analyze_dir(rev, dir, &hooks)
+    }
+
+    fn analyze_dir(&self, rev: &str, dir: BString, hooks: &Hooks<'_>) -> Result<Analysis> {
+        let repo = self.repository()?;
+        let (tree_id, commit_id) = resolve(&repo, rev)?;
+        let gengo = self.for_tree(&repo, &tree_id)?;
+        gengo.analyze_tree(repo, tree_id, commit_id, dir, hooks)
+    }
+
+    /// Analyzes a tree of the repository, and the submodules in it.
+    fn analyze_tree(
+        &self,
+        repo: gix::Repository,
+        tree_id: gix::ObjectId,
+        commit_id: Option<gix::ObjectId>,
+        dir: BString,
+        hooks: &Hooks<'_>,
+    ) -> Result<Analysis> {
+        let mut pending = VecDeque::from([Submodule {
+            root: BString::default(),
+            dir,
+            repo,
+            tree_id,
+            commit_id,
+            depth: 0,
+        }]);
+        let mut all_results = Results::default();
+        let mut submodule_commits = IndexMap::new();
+        // NOTE Submodules with the same tree are analyzed once, and their
+        //      results are reused under each root. Objects are addressed by
+        //      their contents, so the tree id is enough to identify them,
+        //      even when each path has its own clone of the repository.
+        let mut analyzed: HashMap<(gix::ObjectId, BString), Results> = HashMap::new();
+        // NOTE Submodules are analyzed concurrently in batches of at most
+        //      `self.threads`. Nested submodules are added to the queue as
+        //      their parents are analyzed.
+        while !pending.is_empty() {
+            if hooks.is_interrupted() {
+                break;
+            }
+            let batch: Vec<_> = pending.drain(..pending.len().min(self.threads)).collect();
+            let mut keys = HashSet::new();
+            let (batch, duplicates): (Vec<_>, Vec<_>) = batch.into_iter().partition(|submodule| {
+                let key = submodule.key();
+                submodule.root.is_empty() || (!analyzed.contains_key(&key) && keys.insert(key))
+            });
+            let keys: Vec<_> = batch.iter().map(Submodule::key).collect();
+            // NOTE The submodules in a batch share the thread budget, so that
+            //      at most `self.threads` files are analyzed at once.
+            let threads = (self.threads / batch.len().max(1)).max(1);
+            let results = if batch.len() == 1 {
+                batch
+                    .into_iter()
+                    .map(|submodule| self.analyze_submodule(submodule, threads, hooks))
+                    .collect()
+            } else {
+                std::thread::scope(|scope| {
+                    let handles: Vec<_> = batch
+                        .into_iter()
+                        .map(|submodule| {
+                            scope.spawn(|| self.analyze_submodule(submodule, threads, hooks))
+                        })
+                        .collect();
+                    handles
+                        .into_iter()
+                        .map(|handle| handle.join().expect("submodule analysis should not panic"))
+                        .collect::<Vec<_>>()
+                })
+            };
+            for (key, result) in keys.into_iter().zip(results) {
+                let (root, results, submodules) = result?;
+                if !root.is_empty() {
+                    analyzed.insert(key, results.clone());
+                }
+                all_results.extend_under(root.as_ref(), results);
+                self.queue_submodules(
+                    submodules,
+                    &mut pending,
+                    &mut all_results,
+                    &mut submodule_commits,
+                );
+            }
+            for duplicate in duplicates {
+                let results = analyzed[&duplicate.key()].clone();
+                let submodules = self.nested_submodules(&duplicate)?;
+                all_results.extend_under(duplicate.root.as_ref(), results);
+                self.queue_submodules(
+                    submodules,
+                    &mut pending,
+                    &mut all_results,
+                    &mut submodule_commits,
+                );
+            }
+        }
+
+        Ok(Analysis {
+            results: all_results,
+            tree_id,
+            commit_id,
+            submodule_commits,
+            by_path: Default::default(),
+        })
+    }
+
+    /// Adds submodules to the queue of submodules to analyze, and records the
+    /// commits they are pinned to. Submodules that are nested too deeply are
+    /// skipped instead.
+    fn queue_submodules(
+        &self,
+        submodules: Vec<Submodule>,
+        pending: &mut VecDeque<Submodule>,
+        results: &mut Results,
+        commits: &mut IndexMap<BString, gix::ObjectId>,
+    ) {
+        for submodule in submodules {
+            let path = submodule.root[..submodule.root.len() - 1].as_bstr();
+            if let Some(commit_id) = submodule.commit_id {
+                commits.insert(path.to_owned(), commit_id);
+            }
+            if submodule.depth <= self.max_submodule_depth {
+                pending.push_back(submodule);
+                continue;
+            }
+            results.push(path, submodule.tree_id, None);
+            if let Some(last) = results.entries.last_mut() {
+                last.skipped = Some(SkipReason::MaxSubmoduleDepth);
+            }
+        }
+    }
+
+    /// Gets the submodules nested in a submodule, without analyzing it.
+    fn nested_submodules(&self, submodule: &Submodule) -> Result<Vec<Submodule>> {
+        let Submodule {
+            root,
+            dir,
+            repo,
+            tree_id,
+            depth,
+            ..
+        } = submodule;
+        if self.submodules == SubmodulePolicy::Ignore {
+            return Ok(Vec::new());
+        }
+        let index = index_from_tree(repo, tree_id)?;
+        Self::submodules(repo, &index, root.as_ref(), dir.as_ref(), depth + 1)
+    }
+
+    /// Analyzes a single repository or submodule. Returns the root of the
+    /// submodule, its results, and the submodules that it contains.
+    fn analyze_submodule(
+        &self,
+        submodule: Submodule,
+        threads: usize,
+        hooks: &Hooks<'_>,
+    ) -> Result<(BString, Results, Vec<Submodule>)> {
+        let (prepared, submodules) = self.prepare_submodule(submodule)?;
+        let PreparedSubmodule {
+            root,
+            repo,
+            state,
+            mut results,
+            vendored,
+        } = prepared;
+        hooks.add_total(
+            results
+                .entries
+                .iter()
+                .filter(|e| e.skipped.is_none())
+                .count(),
+        );
+        let repo = repo.into_sync();
+        self.analyze_index(&repo, &mut results, state, vendored, threads, hooks)?;
+        Ok((root, results, submodules))
+    }
+
+    /// Reads the index and attributes of a repository or submodule, without
+    /// analyzing its files. Also returns the submodules that it contains.
+    fn prepare_submodule(
+        &self,
+        submodule: Submodule,
+    ) -> Result<(PreparedSubmodule, Vec<Submodule>)> {
+        let Submodule {
+            root,
+            dir,
+            repo,
+            tree_id,
+            depth,
+            ..
+        } = submodule;
+        let is_submodule = !root.is_empty();
+        let (state, index) = GitState::new(&repo, &tree_id, &self.overrides)?;
+        // NOTE The directory can also be inside of a submodule.
+        if !is_submodule
+            && !dir.is_empty()
+            && !index.entries().iter().any(|e| {
+                let path = e.path(&index);
+                is_under(path, dir.as_ref())
+                    || (e.mode == gix::index::entry::Mode::COMMIT && is_under(dir.as_ref(), path))
+            })
+        {
+            return Err(Box::new(
+                Error::new(ErrorKind::NoPath).with_context(dir.to_string()),
+            ));
+        }
+        let submodules = match self.submodules {
+            SubmodulePolicy::Ignore => Vec::new(),
+            SubmodulePolicy::Recurse | SubmodulePolicy::RecurseAsVendored => {
+                Self::submodules(&repo, &index, root.as_ref(), dir.as_ref(), depth + 1)?
+            }
+        };
+        let results = Results::from_index(index, dir.as_ref(), |path| {
+            self.is_excluded(root.as_ref(), path)
+        });
+        let vendored = is_submodule && self.submodules == SubmodulePolicy::RecurseAsVendored;
+        let prepared = PreparedSubmodule {
+            root,
+            repo,
+            state,
+            results,
+            vendored,
+        };
+        Ok((prepared, submodules))
+    }
+
+    /// Analyzes only the files that were added or modified between two
+    /// revisions. Deleted files are excluded, and renamed files are analyzed
+    /// at their new path.
+    ///
+    /// Submodules are not recursed into.
+    pub fn analyze_diff(&self, base_rev: &str, head_rev: &str) -> Result<Analysis> {
+        let repo = self.repository()?;
+        let base_tree_id = tree_id(&repo, base_rev)?;
+        let (head_tree_id, head_commit_id) = resolve(&repo, head_rev)?;
+        let gengo = self.for_tree(&repo, &head_tree_id)?;
+
+        let base_index = index_from_tree(&repo, &base_tree_id)?;
+        let (state, index) = GitState::new(&repo, &head_tree_id, &gengo.overrides)?;
+        let mut results = Results::from_index(index, BStr::new(""), |path| {
+            gengo.is_excluded(BStr::new(""), path)
+        });
+        let Results {
+            entries,
+            path_storage,
+        } = &mut results;
+        entries.retain(|entry| {
+            base_index
+                .entry_by_path(path_storage[entry.path.clone()].as_bstr())
+                .is_none_or(|base_entry| base_entry.id != entry.id)
+        });
+
+        let interrupt = AtomicBool::new(false);
+        let mut progress = |_, _| {};
+        let hooks = Hooks::new(&interrupt, &mut progress);
+        let repo = repo.into_sync();
+        gengo.analyze_index(&repo, &mut results, state, false, gengo.threads, &hooks)?;
+
+        Ok(Analysis {
+            results,
+            tree_id: head_tree_id,
+            commit_id: head_commit_id,
+            submodule_commits: IndexMap::new(),
+            by_path: Default::default(),
+        })
+    }
+
+    /// Analyzes the files in the repository's index, as they are in the
+    /// worktree. This includes staged files and unstaged changes to them,
+    /// which is useful for hooks that run before a commit. Files that are
+    /// modified in the worktree are read from the worktree, and the rest are
+    /// read from the object database. Files that were deleted from the
+    /// worktree are analyzed as they are staged. Untracked files are left
+    /// out, unless `Builder::untracked` is enabled.
+    ///
+    /// Attributes are read from the worktree, falling back to the index.
+    /// The revision of the analysis is the null id, since the index has no
+    /// tree. Submodules are not recursed into.
+    ///
+    /// Returns an error if the repository is bare.
+    pub fn analyze_worktree(&self) -> Result<Analysis> {
+        use gix::index::entry::{stat, Mode, Stat};
+
+        let repo = self.repository()?;
+        let Some(work_dir) = repo.work_dir() else {
+            return Err(Box::new(Error::new(ErrorKind::NoWorktree)));
+        };
+        let gengo = self.for_worktree(work_dir, repo.object_hash())?;
+        let index = repo.open_index()?.into_parts().0;
+
+        // NOTE Files whose stat information matches the index are unmodified,
+        //      unless they were modified in the same second that the index
+        //      was written. Other files are hashed to check if they changed.
+        let options = stat::Options::default();
+        let mut modified = HashMap::new();
+        for entry in index
+            .entries()
+            .iter()
+            .filter(|e| matches!(e.mode, Mode::FILE | Mode::FILE_EXECUTABLE))
+        {
+            let path = entry.path(&index);
+            let Ok(filepath) = gix::path::try_from_bstr(path) else {
+                continue;
+            };
+            let filepath = work_dir.join(filepath);
+            let Ok(metadata) = std::fs::symlink_metadata(&filepath) else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            if entry.stat.matches(&Stat::from_fs(&metadata)?, options)
+                && !entry.stat.is_racy(index.timestamp(), options)
+            {
+                continue;
+            }
+            let contents = std::fs::read(&filepath)?;
+            let id =
+                gix::objs::compute_hash(repo.object_hash(), gix::object::Kind::Blob, &contents);
+            if id != entry.id {
+                modified.insert(path.to_owned(), id);
+            }
+        }
+
+        let untracked = if gengo.untracked {
+            gengo.untracked_files(work_dir, &index, repo.object_hash())?
+        } else {
+            Vec::new()
+        };
+
+        let (state, index) = GitState::from_index(
+            &repo,
+            index,
+            gix::worktree::stack::state::attributes::Source::WorktreeThenIdMapping,
+            &gengo.overrides,
+        )?;
+        let mut results = Results::from_index(index, BStr::new(""), |path| {
+            gengo.is_excluded(BStr::new(""), path)
+        });
+        let Results {
+            entries,
+            path_storage,
+        } = &mut results;
+        for entry in entries.iter_mut() {
+            if let Some(id) = modified.get(path_storage[entry.path.clone()].as_bstr()) {
+                entry.id = *id;
+                entry.modified = true;
+            }
+        }
+        for (path, id) in untracked {
+            if gengo.is_excluded(BStr::new(""), path.as_ref()) {
+                continue;
+            }
+            results.push(path.as_ref(), id, None);
+            if let Some(entry) = results.entries.last_mut() {
+                entry.modified = true;
+                entry.untracked = true;
+            }
+        }
+
+        let interrupt = AtomicBool::new(false);
+        let mut progress = |_, _| {};
+        let hooks = Hooks::new(&interrupt, &mut progress);
+        let object_hash = repo.object_hash();
+        let repo = repo.into_sync();
+        gengo.analyze_index(&repo, &mut results, state, false, gengo.threads, &hooks)?;
+
+        Ok(Analysis {
+            results,
+            tree_id: gix::ObjectId::null(object_hash),
+            commit_id: None,
+            submodule_commits: IndexMap::new(),
+            by_path: Default::default(),
+        })
+    }
+
+    /// Resolves a revision without analyzing it, to find mistakes in the
+    /// revision before doing any heavy work.
+    ///
+    /// Returns the id of the commit that the revision points to, or the id
+    /// of its tree if it isn't a commit. The id can be analyzed instead of
+    /// the revision, so the revision is only resolved once, even if the
+    /// branch it names moves.
+    pub fn resolve(&self, rev: &str) -> Result<gix::ObjectId> {
+        let repo = self.repository()?;
+        let (tree_id, commit_id) = resolve(&repo, rev)?;
+        Ok(commit_id.unwrap_or(tree_id))
+    }
+
+    /// Applies the `.gengo.toml` file at the root of a tree, if
+    /// `Builder::repository_config` is enabled.
+    fn for_tree(&self, repo: &gix::Repository, tree_id: &gix::oid) -> Result<Cow<'_, Self>> {
+        if self.settings.is_none() {
+            return Ok(Cow::Borrowed(self));
+        }
+        self.with_config(Config::from_tree(repo, tree_id)?)
+    }
+
+    /// Applies the `.gengo.toml` file at the root of a worktree, if
+    /// `Builder::repository_config` is enabled.
+    fn for_worktree(&self, dir: &Path, object_hash: gix::hash::Kind) -> Result<Cow<'_, Self>> {
+        if self.settings.is_none() {
+            return Ok(Cow::Borrowed(self));
+        }
+        self.with_config(Config::from_worktree(dir, object_hash)?)
+    }
+
+    fn with_config(&self, config: Option<(gix::ObjectId, Config)>) -> Result<Cow<'_, Self>> {
+        let (Some(settings), Some((config_id, config))) = (&self.settings, config) else {
+            return Ok(Cow::Borrowed(self));
+        };
+        let Configured {
+            analyzers,
+            read_limit,
+            documentation,
+            generated,
+            vendored,
+            exclude,
+        } = Settings::clone(settings).configure(config)?;
+        Ok(Cow::Owned(Self {
+            analyzers,
+            read_limit,
+            documentation,
+            generated,
+            vendored,
+            exclude,
+            config_id: Some(config_id),
+            ..self.clone()
+        }))
+    }
+
+    /// Gets the repository to analyze. Returns an error if this instance was
+    /// built without a repository.
+    fn repository(&self) -> Result<gix::Repository> {
+        match &self.repository {
+            Some(repository) => Ok(repository.to_thread_local()),
+            None => Err(Box::new(Error::new(ErrorKind::NoRepository))),
+        }
+    }
+
+    /// Finds the files in the worktree that aren't in the index, or in a
+    /// submodule, and aren't ignored. Returns their paths and the ids of
+    /// their contents.
+    fn untracked_files(
+        &self,
+        work_dir: &Path,
+        index: &gix::index::State,
+        object_hash: gix::hash::Kind,
+    ) -> Result<Vec<(BString, gix::ObjectId)>> {
+        use file_source::{Fs, SymlinkPolicy};
+        use gix::index::entry::Mode;
+
+        let tracked: HashSet<_> = index.entries().iter().map(|e| e.path(index)).collect();
+        let submodules: Vec<_> = index
+            .entries()
+            .iter()
+            .filter(|e| e.mode == Mode::COMMIT)
+            .map(|e| e.path(index))
+            .collect();
+        let fs = Fs::new(work_dir, true).symlinks(SymlinkPolicy::Skip);
+        let mut untracked = Vec::new();
+        // NOTE Only the files that are left after filtering are read.
+        for filepath in fs.paths() {
+            let path = gix::path::to_unix_separators_on_windows(gix::path::into_bstr(&filepath));
+            let path = path.as_ref().as_bstr();
+            if tracked.contains(path) || submodules.iter().any(|dir| is_under(path, dir)) {
+                continue;
+            }
+            let Ok(contents) = std::fs::read(work_dir.join(&filepath)) else {
+                continue;
+            };
+            let id = gix::objs::compute_hash(object_hash, gix::object::Kind::Blob, &contents);
+            untracked.push((path.to_owned(), id));
+        }
+        Ok(untracked)
+    }
+
+    /// Analyzes a single file at the given revision, and explains how it
+    /// was analyzed.
+    ///
+    /// Returns an error if the file doesn't exist at the revision.
+    pub fn explain<P: AsRef<Path>>(&self, rev: &str, filepath: P) -> Result<Explanation> {
+        use gix::index::entry::Mode;
+
+        let filepath = filepath.as_ref();
+        let repo = self.repository()?;
+        let tree_id = tree_id(&repo, rev)?;
+        let gengo = self.for_tree(&repo, &tree_id)?;
+        let (mut state, index) = GitState::new(&repo, &tree_id, &gengo.overrides)?;
+        let Some(index_entry) = index
+            .entry_by_path(gix::path::into_bstr(filepath).as_ref())
+            .filter(|e| matches!(e.mode, Mode::FILE | Mode::FILE_EXECUTABLE))
+        else {
+            return Err(Box::new(
+                Error::new(ErrorKind::NoPath).with_context(filepath.display().to_string()),
+            ));
+        };
+
+        let mut blob_entry = BlobEntry {
+            path: 0..0,
+            id: index_entry.id,
+            result: None,
+            unresolved_language: None,
+            skipped: None,
+            unidentified: None,
+            modified: false,
+            executable: index_entry.mode == Mode::FILE_EXECUTABLE,
+            untracked: false,
+        };
+        gengo.analyze_blob(filepath, &repo, &mut state, &mut blob_entry, false)?;
+
+        let blob = match repo.find_object(index_entry.id) {
+            Ok(blob) => blob,
+            Err(err @ gix::object::find::existing::Error::NotFound { .. }) => {
+                return Err(Box::new(
+                    Error::with_source(ErrorKind::ObjectMissing, err)
+                        .with_context(index_entry.id.to_string()),
+                ));
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let contents = &blob.data[..blob.data.len().min(gengo.read_limit)];
+        let languages = |found: languages::analyzer::Found| {
+            found
+                .into_iter()
+                .filter_map(|key| gengo.analyzers.get(&key).cloned())
+                .collect()
+        };
+        let mut filename_matches: Vec<_> = languages(gengo.analyzers.by_filename(filepath));
+        filename_matches.extend(languages(gengo.analyzers.by_filepath_pattern(filepath)));
+        let attributes = state
+            .attributes(filepath, &repo, &gengo.overrides)?
+            .into_iter()
+            .flatten()
+            .map(|info| {
+                let value = match info.assignment.state {
+                    StateRef::Set => "set".to_string(),
+                    StateRef::Unset => "unset".to_string(),
+                    StateRef::Value(v) => v.as_bstr().to_string(),
+                    StateRef::Unspecified => unreachable!("unspecified attributes are skipped"),
+                };
+                (info.assignment.name.as_str().to_string(), value)
+            })
+            .collect();
+
+        Ok(Explanation {
+            shebang_matches: languages(gengo.analyzers.by_shebang(contents)),
+            filename_matches,
+            extension_matches: languages(gengo.analyzers.by_extension(filepath)),
+            attributes,
+            entry: blob_entry.result,
+        })
+    }
 
-        let (state, index) = GitState::new(&repo, &tree_id)?;
-        let mut results = Results::from_index(index);
+    /// Finds the submodules in the index that have been checked out, and that
+    /// are in `dir` or contain `dir`.
+    fn submodules(
+        repo: &gix::Repository,
+        index: &gix::index::State,
+        root: &BStr,
+        dir: &BStr,
+        depth: usize,
+    ) -> Result<Vec<Submodule>> {
+        use gix::index::entry::Mode;
 
-        self.analyze_index(&repo.into_sync(), &mut results, state)?;
+        // NOTE Submodules are commits in the index. Checking for them first
+        //      avoids reading `.gitmodules`, which needs a commit that empty
+        //      repositories don't have.
+        if !index.entries().iter().any(|e| e.mode == Mode::COMMIT) {
+            return Ok(Vec::new());
+        }
+        let Some(submodules) = repo.submodules()? else {
+            return Ok(Vec::new());
+        };
+        let mut found = Vec::new();
+        for submodule in submodules {
+            let path = submodule.path()?;
+            let submodule_dir = if is_under(path.as_ref(), dir) {
+                BString::default()
+            } else if is_under(dir, path.as_ref()) {
+                dir[path.len() + 1..].into()
+            } else {
+                continue;
+            };
+            let Some(entry) = index.entry_by_path(path.as_ref()) else {
+                continue;
+            };
+            if entry.mode != Mode::COMMIT {
+                continue;
+            }
+            // NOTE Submodules that aren't initialized, or that don't have the
+            //      pinned commit, can't be analyzed.
+            let Some(submodule_repo) = submodule.open()? else {
+                continue;
+            };
+            let Ok(commit) = submodule_repo.find_object(entry.id) else {
+                continue;
+            };
+            let tree_id = commit.peel_to_tree()?.id;
 
-        Ok(Analysis(results))
+            let mut submodule_root = root.to_owned();
+            submodule_root.extend_from_slice(path.as_ref());
+            submodule_root.push(b'/');
+            found.push(Submodule {
+                root: submodule_root,
+                dir: submodule_dir,
+                repo: submodule_repo,
+                tree_id,
+                commit_id: Some(entry.id),
+                depth,
+            });
+        }
+        Ok(found)
     }
 
     fn analyze_index(
@@ -133,21 +1311,34 @@ impl Gengo {
         repo: &gix::ThreadSafeRepository,
         results: &mut Results,
         state: GitState,
+        is_submodule: bool,
+        threads: usize,
+        hooks: &Hooks<'_>,
     ) -> Result<()> {
+        let Results {
+            entries,
+            path_storage,
+        } = results;
         gix::parallel::in_parallel_with_slice(
-            &mut results.entries,
-            None,
+            entries,
+            Some(threads),
             move |_| (state.clone(), repo.to_thread_local()),
             |entry, (state, repo), _, should_interrupt| {
-                if should_interrupt.load(Ordering::Relaxed) {
+                if entry.skipped.is_some() {
+                    return Ok(());
+                }
+                if should_interrupt.load(Ordering::Relaxed) || hooks.is_interrupted() {
+                    entry.skipped = Some(SkipReason::Interrupted);
                     return Ok(());
                 }
-                let Ok(path) =
-                    gix::path::try_from_bstr(entry.index_entry.path_in(&results.path_storage))
+                let Ok(path) = gix::path::try_from_bstr(path_storage[entry.path.clone()].as_bstr())
                 else {
+                    entry.skipped = Some(SkipReason::NonUtf8Path);
                     return Ok(());
                 };
-                self.analyze_blob(path, repo, state, entry)
+                let result = self.analyze_blob(path, repo, state, entry, is_submodule);
+                hooks.increment();
+                result
             },
             || Some(std::time::Duration::from_micros(5)),
             std::convert::identity,
@@ -161,83 +1352,488 @@ impl Gengo {
         repo: &gix::Repository,
         state: &mut GitState,
         result: &mut BlobEntry,
+        is_submodule: bool,
     ) -> Result<()> {
         let filepath = filepath.as_ref();
-        let blob = repo.find_object(result.index_entry.id)?;
-        let contents = blob.data.as_slice();
-        state
-            .attr_stack
-            .at_path(filepath, Some(false), |id, buf| {
-                repo.objects.find_blob(id, buf)
-            })?
-            .matching_attributes(&mut state.attr_matches);
-
-        let mut attrs = [None, None, None, None, None];
-        state
-            .attr_matches
-            .iter_selected()
-            .zip(attrs.iter_mut())
-            .for_each(|(info, slot)| {
-                *slot =
-                    (info.assignment.state != gix::attrs::StateRef::Unspecified).then_some(info);
-            });
+        let attrs = state.attributes(filepath, repo, &self.overrides)?;
+        // NOTE Files that git treats as binary, like with the `binary` macro,
+        //      aren't read for content heuristics, so they aren't cached with
+        //      the blobs that were.
+        let binary = attrs[ATTRIBUTE_COUNT]
+            .as_ref()
+            .is_some_and(|info| info.assignment.state == StateRef::Unset);
+        let cache = self.cache.as_ref().filter(|_| !binary);
+        let cached = cache
+            .and_then(|cache| cache.get(&result.id, filepath, result.executable, self.config_id));
+        let blob = match cached {
+            Some(blob) => blob,
+            None => {
+                let Some(blob) = self.read_blob(
+                    filepath,
+                    repo,
+                    result.id,
+                    result.modified,
+                    binary,
+                    result.executable,
+                )?
+                else {
+                    result.skipped = Some(SkipReason::ObjectMissing);
+                    return Ok(());
+                };
+                if let Some(cache) = cache {
+                    cache.insert(result.id, blob.clone());
+                }
+                blob
+            }
+        };
 
-        let lang_override = attrs[0]
+        let lang_name = attrs[0]
             .as_ref()
             .and_then(|info| match info.assignment.state {
-                StateRef::Value(v) => v.as_bstr().to_str().ok().map(|s| s.replace('-', " ")),
+                StateRef::Value(v) => v.as_bstr().to_str().ok().map(str::to_string),
                 _ => None,
-            })
-            .and_then(|s| self.analyzers.get(&s));
-
-        let language =
-            lang_override.or_else(|| self.analyzers.pick(filepath, contents, self.read_limit));
-
-        let language = if let Some(language) = language {
-            language
-        } else {
-            return Ok(());
-        };
+            });
+        let lang_override = lang_name.as_deref().and_then(|name| {
+            let language = self.analyzers.resolve(name);
+            if language.is_none() {
+                let suggestion = self
+                    .analyzers
+                    .suggest(name)
+                    .map(|language| language.name().to_string());
+                result.unresolved_language = Some((name.to_string(), suggestion));
+            }
+            language.map(|language| (language, Detection::Attribute))
+        });
 
-        // NOTE Unspecified attributes are None, so `state.is_set()` is
-        //      implicitly `!state.is_unset()`.
+        let language = lang_override.or_else(|| {
+            blob.language
+                .as_ref()
+                .map(|(language, detection)| (language, *detection))
+        });
+        // NOTE Unspecified attributes are None, so the heuristics only decide
+        //      when an attribute is neither set nor unset.
         let generated = attrs[1]
             .as_ref()
-            .map(|info| info.assignment.state.is_set())
-            .unwrap_or_else(|| self.is_generated(filepath, contents));
+            .and_then(|info| attributes::flag(info.assignment.state))
+            .unwrap_or(blob.generated);
         let documentation = attrs[2]
             .as_ref()
-            .map(|info| info.assignment.state.is_set())
-            .unwrap_or_else(|| self.is_documentation(filepath, contents));
+            .and_then(|info| attributes::flag(info.assignment.state))
+            .unwrap_or(blob.documentation);
         let vendored = attrs[3]
             .as_ref()
-            .map(|info| info.assignment.state.is_set())
-            .unwrap_or_else(|| self.is_vendored(filepath, contents));
+            .and_then(|info| attributes::flag(info.assignment.state))
+            .unwrap_or(is_submodule || blob.vendored);
+        let detectable_attr = attrs[4]
+            .as_ref()
+            .and_then(|info| attributes::flag(info.assignment.state));
+        let binary = blob.binary;
 
-        let detectable = match language.category() {
-            Category::Data | Category::Prose => false,
-            Category::Programming | Category::Markup | Category::Query => {
-                !(generated || documentation || vendored)
-            }
+        let Some((language, detection)) = language else {
+            result.unidentified = Some(Unidentified {
+                size: blob.size,
+                lines: blob.lines,
+                chars: blob.chars,
+                binary,
+                detectable: detectable_attr.unwrap_or(!(generated || documentation || vendored)),
+            });
+            return Ok(());
         };
-        let detectable = attrs[4]
-            .as_ref()
-            .map(|info| info.assignment.state.is_set())
-            .unwrap_or(detectable);
 
-        let size = contents.len();
+        let attributes = AttributeState::new(std::array::from_fn(|i| {
+            attrs[i]
+                .as_ref()
+                .and_then(|info| attributes::flag(info.assignment.state))
+        }));
+
+        let detectable = self.is_detectable(language, binary, generated, documentation, vendored);
+        let detectable = detectable_attr.unwrap_or(detectable);
+
         let entry = Entry {
             language: language.clone(),
-            size,
+            size: blob.size,
+            lines: blob.lines,
+            chars: blob.chars,
             detectable,
             generated,
             documentation,
             vendored,
+            binary,
+            executable: result.executable,
+            untracked: result.untracked,
+            attributes,
+            embedded_languages: blob.embedded_languages,
+            detection,
         };
         result.result = Some(entry);
         Ok(())
     }
 
+    /// Reads a blob and analyzes the parts that don't depend on attributes.
+    /// Returns `None` if the blob is missing from the object database.
+    ///
+    /// If the blob is `binary`, only its size is read, and it is identified
+    /// by its path. If the blob is `executable`, it is read even if the
+    /// detector doesn't read contents, so that its shebang can be checked.
+    fn read_blob(
+        &self,
+        filepath: &Path,
+        repo: &gix::Repository,
+        id: gix::ObjectId,
+        modified: bool,
+        binary: bool,
+        executable: bool,
+    ) -> Result<Option<CachedBlob>> {
+        use gix::odb::find::existing::Error as FindError;
+
+        let reads_contents = (self.detector.reads_contents() || executable) && !binary;
+
+        // NOTE If the detector doesn't read the contents, only the size of
+        //      the blob is read. Blobs that are too large are only partially
+        //      read, or not at all, so their lines aren't counted.
+        let blob;
+        let loose_head;
+        let worktree_contents;
+        let partial;
+        let (contents, size) = if modified {
+            let path = repo
+                .work_dir()
+                .expect("modified files should be in a worktree")
+                .join(filepath);
+            let size;
+            (worktree_contents, size, partial) =
+                match self.read_worktree_file(&path, reads_contents) {
+                    Ok(read) => read,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                    Err(err) => return Err(err.into()),
+                };
+            (worktree_contents.as_slice(), size)
+        } else {
+            // NOTE gix can't stream blobs, so only the start of loose blobs,
+            //      including those in alternates, that are too large is read.
+            //      Packed blobs that are too
+            //      large are identified by their path, like when contents
+            //      aren't read.
+            let header_size = if reads_contents && self.max_blob_size.is_none() {
+                None
+            } else {
+                match repo.find_header(id) {
+                    Ok(header) => Some(header.size() as usize),
+                    Err(FindError::NotFound { .. }) => return Ok(None),
+                    Err(err) => return Err(err.into()),
+                }
+            };
+            partial = reads_contents && header_size.is_some_and(|size| self.is_too_large(size));
+            match header_size {
+                Some(size) if !reads_contents => (&[][..], size),
+                Some(size) if partial => {
+                    loose_head = self.read_loose_head(repo, &id)?.unwrap_or_default();
+                    (loose_head.as_slice(), size)
+                }
+                _ => {
+                    blob = match repo.find_object(id) {
+                        Ok(blob) => blob,
+                        Err(FindError::NotFound { .. }) => return Ok(None),
+                        Err(err) => return Err(err.into()),
+                    };
+                    (blob.data.as_slice(), blob.data.len())
+                }
+            }
+        };
+        let read = BlobRead {
+            size,
+            binary,
+            partial,
+            reads_contents,
+            executable,
+        };
+        Ok(Some(self.classify(filepath, contents, read)))
+    }
+
+    /// Reads a file in the worktree, returning its contents, its size, and if
+    /// only the first `read_limit` bytes were read because it is too large.
+    fn read_worktree_file(
+        &self,
+        path: &Path,
+        reads_contents: bool,
+    ) -> std::io::Result<(Vec<u8>, usize, bool)> {
+        use std::io::Read;
+
+        let size = std::fs::metadata(path)?.len() as usize;
+        if !reads_contents {
+            return Ok((Vec::new(), size, false));
+        }
+        if !self.is_too_large(size) {
+            let contents = std::fs::read(path)?;
+            let size = contents.len();
+            return Ok((contents, size, false));
+        }
+        let mut head = Vec::with_capacity(self.read_limit);
+        std::fs::File::open(path)?
+            .take(self.read_limit as u64)
+            .read_to_end(&mut head)?;
+        Ok((head, size, true))
+    }
+
+    /// Reads the first `read_limit` bytes of a loose object, without
+    /// decompressing the rest of it. The object is looked up in the object
+    /// database of the repository, and then in its alternates. This is
+    /// `None` if the object is packed.
+    fn read_loose_head(&self, repo: &gix::Repository, id: &gix::oid) -> Result<Option<Vec<u8>>> {
+        use std::io::{BufRead, BufReader, Read};
+
+        let hex = id.to_hex().to_string();
+        let store = repo.objects.store_ref();
+        let dirs = std::iter::once(store.path().to_path_buf()).chain(store.alternate_db_paths()?);
+        let mut file = None;
+        for dir in dirs {
+            match std::fs::File::open(dir.join(&hex[..2]).join(&hex[2..])) {
+                Ok(opened) => {
+                    file = Some(opened);
+                    break;
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        let Some(file) = file else {
+            return Ok(None);
+        };
+        // NOTE A loose object is compressed with its header, like `blob 12`,
+        //      which is terminated by a NUL byte.
+        let mut reader = BufReader::new(flate2::read::ZlibDecoder::new(file));
+        let mut header = Vec::new();
+        reader.by_ref().take(64).read_until(0, &mut header)?;
+        if header.last() != Some(&0) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "invalid loose object header",
+            )
+            .into());
+        }
+        let mut head = Vec::with_capacity(self.read_limit);
+        reader.take(self.read_limit as u64).read_to_end(&mut head)?;
+        Ok(Some(head))
+    }
+}
+
+impl Gengo {
+    /// Analyzes the files of any source, like a directory that isn't a git
+    /// repository or an archive.
+    ///
+    /// Files are analyzed with the same heuristics as the files of a
+    /// revision, but git attributes don't apply, since they are specific to
+    /// the repository. Excluded files are skipped. The revision of the
+    /// analysis is the null id, and the id of each file is the hash of its
+    /// contents.
+    ///
+    /// This is the only way to analyze files with an instance from
+    /// `Builder::build_without_repository`.
+    pub fn analyze_source<'repo, S: FileSource<'repo>>(
+        &self,
+        source: &'repo S,
+    ) -> Result<Analysis> {
+        #[cfg(feature = "git")]
+        let object_hash = self
+            .repository
+            .as_ref()
+            .map_or(gix_hash::Kind::Sha1, |repo| {
+                repo.to_thread_local().object_hash()
+            });
+        #[cfg(not(feature = "git"))]
+        let object_hash = gix_hash::Kind::Sha1;
+        let reads_contents = self.detector.reads_contents();
+        let mut results = Results::default();
+        for (filepath, contents) in source.files()? {
+            let path = gix_path::into_bstr(filepath.as_ref());
+            if self.is_excluded(BStr::new(""), path.as_ref()) {
+                continue;
+            }
+            let id = gix_object::compute_hash(object_hash, gix_object::Kind::Blob, &contents);
+            results.push(path.as_ref(), id, None);
+            let size = contents.len();
+            let partial = reads_contents && self.is_too_large(size);
+            let contents = match (reads_contents, partial) {
+                (false, _) => &[][..],
+                (true, true) => &contents[..self.read_limit.min(size)],
+                (true, false) => &contents[..],
+            };
+            let read = BlobRead {
+                size,
+                binary: false,
+                partial,
+                reads_contents,
+                executable: false,
+            };
+            let blob = self.classify(&filepath, contents, read);
+            let entry = results.entries.last_mut().expect("an entry was pushed");
+            let Some((language, detection)) = blob.language else {
+                entry.unidentified = Some(Unidentified {
+                    size: blob.size,
+                    lines: blob.lines,
+                    chars: blob.chars,
+                    binary: blob.binary,
+                    detectable: !(blob.generated || blob.documentation || blob.vendored),
+                });
+                continue;
+            };
+            let detectable = self.is_detectable(
+                &language,
+                blob.binary,
+                blob.generated,
+                blob.documentation,
+                blob.vendored,
+            );
+            entry.result = Some(Entry {
+                language,
+                size: blob.size,
+                lines: blob.lines,
+                chars: blob.chars,
+                detectable,
+                generated: blob.generated,
+                documentation: blob.documentation,
+                vendored: blob.vendored,
+                binary: blob.binary,
+                executable: false,
+                untracked: false,
+                attributes: AttributeState::default(),
+                embedded_languages: blob.embedded_languages,
+                detection,
+            });
+        }
+
+        Ok(Analysis {
+            results,
+            tree_id: gix_hash::ObjectId::null(object_hash),
+            commit_id: None,
+            submodule_commits: IndexMap::new(),
+            by_path: Default::default(),
+        })
+    }
+
+    /// Analyzes the parts of a file that don't depend on attributes. Only
+    /// the size is used if the contents aren't read.
+    ///
+    /// The shebang of an `executable` file takes precedence over a detector
+    /// that doesn't read contents.
+    fn classify(&self, filepath: &Path, contents: &[u8], read: BlobRead) -> CachedBlob {
+        let BlobRead {
+            size,
+            binary,
+            partial,
+            reads_contents,
+            executable,
+        } = read;
+        let replaced = match &self.blob_reader {
+            Some(reader) if reads_contents => reader.read(filepath, contents),
+            _ => None,
+        };
+        let (contents, size) = match &replaced {
+            Some(replaced) => (replaced.as_slice(), replaced.len()),
+            None => (contents, size),
+        };
+        // NOTE A Git LFS pointer is identified by its path, like a file whose
+        //      contents aren't read, and has the size of the file it points to.
+        let (contents, size) = match lfs::Pointer::parse(contents) {
+            Some(pointer) => (&[][..], pointer.size()),
+            None => (contents, size),
+        };
+        let shebang = (executable && reads_contents && !self.detector.reads_contents())
+            .then(|| self.analyzers.pick_by_shebang(contents))
+            .flatten()
+            .map(|language| (language, Detection::Shebang));
+        let language = shebang
+            .or_else(|| {
+                self.detector
+                    .detect(&self.analyzers, filepath, contents, self.read_limit)
+            })
+            .map(|(language, detection)| (language.clone(), detection));
+        // NOTE Content heuristics only read up to the read limit, but the
+        //      size is always the full length of the blob.
+        let head = &contents[..contents.len().min(self.read_limit)];
+        let binary = binary || binary::is_binary(head);
+        let lines = if binary || partial {
+            0
+        } else {
+            count_lines(contents)
+        };
+        let chars = if binary || partial {
+            0
+        } else {
+            encoding::count_chars(contents)
+        };
+        let embedded_languages = match &language {
+            Some((language, _)) if self.embedded_languages && !binary => {
+                embedded::embedded_languages(&self.analyzers, language, head)
+            }
+            _ => Vec::new(),
+        };
+        CachedBlob {
+            filepath: filepath.to_path_buf(),
+            language,
+            size,
+            lines,
+            chars,
+            binary,
+            executable,
+            config_id: self.config_id,
+            generated: self.is_generated(filepath, head),
+            documentation: self.is_documentation(filepath, head),
+            vendored: self.is_vendored(filepath, head),
+            embedded_languages,
+        }
+    }
+
+    /// Checks if a file counts towards the language statistics when no
+    /// attribute decides it.
+    fn is_detectable(
+        &self,
+        language: &Language,
+        binary: bool,
+        generated: bool,
+        documentation: bool,
+        vendored: bool,
+    ) -> bool {
+        let category = self
+            .category_overrides
+            .get(language)
+            .unwrap_or(language.category());
+        !binary
+            && self.detectable_categories.contains(category)
+            && !(generated || documentation || vendored)
+    }
+
+    /// Checks if a blob is larger than `max_blob_size`.
+    fn is_too_large(&self, size: usize) -> bool {
+        self.max_blob_size.is_some_and(|max| size > max)
+    }
+
+    /// Checks if a file in the repository, or in the submodule at `root`,
+    /// is excluded from analysis, either by a glob or by not having one of
+    /// the allowed extensions. Paths that aren't valid UTF-8 are never
+    /// excluded by a glob.
+    fn is_excluded(&self, root: &BStr, path: &BStr) -> bool {
+        if let Some(extensions) = &self.only_extensions {
+            let extension = path
+                .rfind_byte(b'.')
+                .filter(|&i| !path[i..].contains(&b'/'))
+                .and_then(|i| path[i + 1..].to_str().ok());
+            if !extension.is_some_and(|extension| extensions.contains(&extension.to_lowercase())) {
+                return true;
+            }
+        }
+        if self.exclude.is_empty() {
+            return false;
+        }
+        let mut full_path = root.to_owned();
+        full_path.extend_from_slice(path);
+        gix_path::try_from_bstr(full_path.as_bstr()).is_ok_and(|path| {
+            self.exclude
+                .iter()
+                .any(|g| g.matches_path_with(&path, GLOB_MATCH_OPTIONS))
+        })
+    }
+
     /// Guesses if a file is generated.
     pub fn is_generated<P: AsRef<Path>>(&self, filepath: P, contents: &[u8]) -> bool {
         self.generated.is_generated(filepath, contents)
@@ -252,15 +1848,36 @@ impl Gengo {
     pub fn is_vendored<P: AsRef<Path>>(&self, filepath: P, contents: &[u8]) -> bool {
         self.vendored.is_vendored(filepath, contents)
     }
+
+    /// The maximum number of bytes read from each file for content heuristics.
+    pub fn read_limit(&self) -> usize {
+        self.read_limit
+    }
+}
+
+/// Counts the lines in the contents. The last line doesn't need to end with a
+/// newline.
+fn count_lines(contents: &[u8]) -> usize {
+    let newlines = contents.iter().filter(|&&b| b == b'\n').count();
+    if contents.last().is_some_and(|&b| b != b'\n') {
+        newlines + 1
+    } else {
+        newlines
+    }
 }
 
 /// A single entry in the language statistics.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Entry {
     /// The detected language.
     language: Language,
     /// The size of the file.
     size: usize,
+    /// The number of lines in the file.
+    lines: usize,
+    /// The number of characters in the file.
+    chars: usize,
     /// If the file is detectable (should not be ignored).
     detectable: bool,
     /// If the file was generated.
@@ -269,6 +1886,18 @@ pub struct Entry {
     documentation: bool,
     /// If the file is vendored.
     vendored: bool,
+    /// If the file is binary.
+    binary: bool,
+    /// If the file is executable.
+    executable: bool,
+    /// If the file is untracked.
+    untracked: bool,
+    /// How the attributes were assigned.
+    attributes: AttributeState,
+    /// The languages embedded in the file.
+    embedded_languages: Vec<Language>,
+    /// How the language was detected.
+    detection: Detection,
 }
 
 impl Entry {
@@ -282,23 +1911,181 @@ impl Entry {
         self.size
     }
 
+    /// The number of lines in the file. This is `0` for binary files, and for
+    /// files larger than `Builder::max_blob_size`.
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// The number of characters (Unicode scalar values) in the file, after
+    /// decoding it like the heuristics do. This is `0` for binary files, and
+    /// for files larger than `Builder::max_blob_size`.
+    pub fn chars(&self) -> usize {
+        self.chars
+    }
+
     /// If the file is detectable (should not be ignored).
     pub fn detectable(&self) -> bool {
         self.detectable
     }
 
-    /// If the file was generated.
+    /// If the file was generated, from its path, its contents, or the
+    /// `gengo-generated` attribute. Generated files are not detectable by
+    /// default.
     pub fn generated(&self) -> bool {
         self.generated
     }
 
-    /// If the file is documentation.
+    /// If the file is documentation, from its path or the
+    /// `gengo-documentation` attribute. Documentation is not detectable by
+    /// default.
     pub fn documentation(&self) -> bool {
         self.documentation
     }
 
-    /// If the file is vendored.
+    /// If the file is vendored, from its path, being in a submodule, or the
+    /// `gengo-vendored` attribute. Vendored files are not detectable by
+    /// default.
     pub fn vendored(&self) -> bool {
         self.vendored
     }
+
+    /// If the file is binary. Binary files are never detectable unless the
+    /// `gengo-detectable` attribute is set.
+    pub fn is_binary(&self) -> bool {
+        self.binary
+    }
+
+    /// If the file has the executable bit set in git. Files from other
+    /// sources are never executable.
+    pub fn is_executable(&self) -> bool {
+        self.executable
+    }
+
+    /// If the file isn't tracked by git, which is only the case for files
+    /// found by `Gengo::analyze_worktree` when `Builder::untracked` is
+    /// enabled.
+    pub fn is_untracked(&self) -> bool {
+        self.untracked
+    }
+
+    /// How the `gengo-*` attributes were assigned for the file. These are
+    /// always unspecified for files from other sources than git.
+    pub fn attributes(&self) -> &AttributeState {
+        &self.attributes
+    }
+
+    /// The other languages embedded in the file, in the order they first
+    /// appear, like the scripts and styles of a Vue component, or the code
+    /// blocks of a Markdown file. This is only found for Vue, HTML, and
+    /// Markdown files, when `Builder::embedded_languages` is enabled, and is
+    /// otherwise empty.
+    pub fn embedded_languages(&self) -> &[Language] {
+        &self.embedded_languages
+    }
+
+    /// How the language was detected. This is useful for finding out why a
+    /// file was identified as an unexpected language.
+    pub fn detection_source(&self) -> Detection {
+        self.detection
+    }
+}
+
+/// Formats the language, the size, and the flags that are set, like
+/// `Rust (1234 bytes, detectable)`.
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({} bytes", self.language.name(), self.size)?;
+        let flags = [
+            (self.detectable, "detectable"),
+            (self.generated, "generated"),
+            (self.documentation, "documentation"),
+            (self.vendored, "vendored"),
+            (self.binary, "binary"),
+            (self.executable, "executable"),
+            (self.untracked, "untracked"),
+        ];
+        for (_, flag) in flags.into_iter().filter(|(set, _)| *set) {
+            write!(f, ", {flag}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        contents,
+        expected,
+        case(b"", 0),
+        case(b"one", 1),
+        case(b"one\n", 1),
+        case(b"one\ntwo", 2),
+        case(b"one\r\ntwo\r\n", 2),
+        case(b"one\r\ntwo\nthree", 3),
+        case(b"\n\n", 2)
+    )]
+    fn test_count_lines(contents: &[u8], expected: usize) {
+        assert_eq!(count_lines(contents), expected);
+    }
+
+    #[test]
+    fn test_display_entry() {
+        let analyzers = Analyzers::default();
+        let mut entry = Entry {
+            language: analyzers.get("Rust").unwrap().clone(),
+            size: 1234,
+            lines: 100,
+            chars: 1234,
+            detectable: true,
+            generated: false,
+            documentation: false,
+            vendored: false,
+            binary: false,
+            executable: false,
+            untracked: false,
+            attributes: AttributeState::default(),
+            embedded_languages: Vec::new(),
+            detection: Detection::Extension,
+        };
+        assert_eq!(entry.to_string(), "Rust (1234 bytes, detectable)");
+        entry.detectable = false;
+        entry.generated = true;
+        entry.vendored = true;
+        assert_eq!(entry.to_string(), "Rust (1234 bytes, generated, vendored)");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serialize_entry() {
+        let analyzers = Analyzers::default();
+        let entry = Entry {
+            language: analyzers.get("Rust").unwrap().clone(),
+            size: 12,
+            lines: 1,
+            chars: 12,
+            detectable: true,
+            generated: false,
+            documentation: false,
+            vendored: true,
+            binary: false,
+            executable: false,
+            untracked: false,
+            attributes: AttributeState::default(),
+            embedded_languages: Vec::new(),
+            detection: Detection::Extension,
+        };
+        let json = serde_json::to_string(&entry).unwrap();
+        let language = serde_json::to_string(entry.language()).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                r#"{{"language":{},"size":12,"lines":1,"chars":12,"detectable":true,"generated":false,"documentation":false,"vendored":true,"binary":false,"executable":false,"untracked":false,"attributes":{{"language":"Unspecified","generated":"Unspecified","documentation":"Unspecified","vendored":"Unspecified","detectable":"Unspecified"}},"embedded_languages":[],"detection":"Extension"}}"#,
+                language
+            )
+        );
+    }
 }
@@ -0,0 +1,16 @@
+//! Hooks for reading the contents of blobs.
+use std::path::Path;
+
+/// Replaces the contents of blobs before they are analyzed, like to fetch the
+/// files that Git LFS pointers point to.
+///
+/// The reader can be set with `Builder::blob_reader`. It is only called when
+/// the contents of files are read, so not when the detector doesn't read
+/// contents.
+pub trait BlobReader: Send + Sync {
+    /// Returns the contents to analyze instead of the contents of the blob
+    /// at `filepath`, or `None` to analyze `contents`. Use
+    /// `gengo::lfs::Pointer::parse` to check if `contents` is a Git LFS
+    /// pointer.
+    fn read(&self, filepath: &Path, contents: &[u8]) -> Option<Vec<u8>>;
+}
@@ -0,0 +1,203 @@
+//! Reports in the formats of other tools.
+use crate::analysis::{Analysis, Summary, SummaryOpts};
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// A language in GitHub Linguist's JSON output.
+#[derive(Serialize)]
+struct LinguistLanguage {
+    size: usize,
+    percentage: String,
+    /// Not in Linguist's output.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lines: Option<usize>,
+}
+
+/// Creates the same JSON that `github-linguist --json` outputs: an object
+/// of language names to their sizes in bytes and percentages, from largest
+/// to smallest. Only detectable files are included, like in Linguist.
+///
+/// Each language also has the number of lines, which Linguist doesn't
+/// output, so tools that expect Linguist's format can ignore it.
+pub fn to_linguist_json(analysis: &Analysis) -> String {
+    let opts = SummaryOpts {
+        by_lines: true,
+        ..Default::default()
+    };
+    let lines = analysis.summary_with(opts);
+    linguist_json(&analysis.summary(), Some(&lines))
+}
+
+/// Like `to_linguist_json`, but for a single summary. The sizes in the
+/// summary are used as is.
+pub fn summary_to_linguist_json(summary: &Summary) -> String {
+    linguist_json(summary, None)
+}
+
+fn linguist_json(sizes: &Summary, lines: Option<&Summary>) -> String {
+    let percentages = sizes.percentages();
+    let languages: IndexMap<_, _> = sizes
+        .sorted_by_size()
+        .into_iter()
+        .map(|(language, size)| {
+            let record = LinguistLanguage {
+                size,
+                percentage: format!("{:.2}", percentages[language]),
+                lines: lines.and_then(|lines| lines.get(language)),
+            };
+            (language.name(), record)
+        })
+        .collect();
+    serde_json::to_string(&languages).expect("languages should serialize")
+}
+
+/// Writes a summary as CSV, with a header row, and a row for each language
+/// with its size and percentage, from largest to smallest.
+pub fn summary_to_csv(summary: &Summary) -> String {
+    let percentages = summary.percentages();
+    let mut csv = String::from("language,bytes,percentage\n");
+    for (language, size) in summary.sorted_by_size() {
+        let fields = [
+            csv_field(language.name()),
+            size.to_string(),
+            format!("{:.2}", percentages[language]),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// A single file in a breakdown, like in `github-linguist --breakdown`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct FileRecord {
+    /// The path of the file, relative to the root of the repository.
+    pub path: PathBuf,
+    /// The name of the file's language.
+    pub language: String,
+    /// The size of the file in bytes.
+    pub size: usize,
+    /// If the file is generated.
+    pub generated: bool,
+    /// If the file is vendored.
+    pub vendored: bool,
+    /// If the file is documentation.
+    pub documentation: bool,
+    /// If the file is included in summaries by default.
+    pub detectable: bool,
+}
+
+/// Lists every identified file with its language and flags, sorted by path.
+pub fn breakdown(analysis: &Analysis) -> Vec<FileRecord> {
+    let mut records: Vec<_> = analysis
+        .iter()
+        .map(|(path, entry)| FileRecord {
+            path: path.into_owned(),
+            language: entry.language().name().to_string(),
+            size: entry.size(),
+            generated: entry.generated(),
+            vendored: entry.vendored(),
+            documentation: entry.documentation(),
+            detectable: entry.detectable(),
+        })
+        .collect();
+    records.sort_by(|a, b| a.path.cmp(&b.path));
+    records
+}
+
+/// Writes a breakdown as CSV, with a header row. Paths use `/` as the
+/// separator on every platform.
+pub fn breakdown_to_csv(records: &[FileRecord]) -> String {
+    let mut csv = String::from("path,language,size,generated,vendored,documentation,detectable\n");
+    for record in records {
+        let path = record.path.to_string_lossy().replace('\\', "/");
+        let fields = [
+            csv_field(&path),
+            csv_field(&record.language),
+            record.size.to_string(),
+            record.generated.to_string(),
+            record.vendored.to_string(),
+            record.documentation.to_string(),
+            record.detectable.to_string(),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quotes a CSV field if it contains a separator, quote, or newline.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        field,
+        expected,
+        case("src/main.rs", "src/main.rs"),
+        case("a,b.rs", "\"a,b.rs\""),
+        case("say \"hi\".txt", "\"say \"\"hi\"\".txt\""),
+        case("line\nbreak", "\"line\nbreak\"")
+    )]
+    fn test_csv_field(field: &str, expected: &str) {
+        assert_eq!(csv_field(field), expected);
+    }
+
+    #[test]
+    fn test_summary_to_csv() {
+        let analyzers = crate::Analyzers::from_yaml(
+            r##"
+Rust:
+  category: programming
+  color: "#000000"
+  matchers:
+    extensions:
+      - rs
+"Foo, Bar":
+  category: programming
+  color: "#FFFFFF"
+  matchers:
+    extensions:
+      - foo
+"##,
+        )
+        .unwrap();
+        let mut summary = Summary::default();
+        summary.add(analyzers.get("Foo, Bar").unwrap(), 25);
+        summary.add(analyzers.get("Rust").unwrap(), 75);
+        assert_eq!(
+            summary_to_csv(&summary),
+            "language,bytes,percentage\nRust,75,75.00\n\"Foo, Bar\",25,25.00\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serialize_file_record() {
+        let record = FileRecord {
+            path: PathBuf::from("main.rs"),
+            language: "Rust".to_string(),
+            size: 13,
+            generated: false,
+            vendored: false,
+            documentation: false,
+            detectable: true,
+        };
+        assert_eq!(
+            serde_json::to_string(&record).unwrap(),
+            r#"{"path":"main.rs","language":"Rust","size":13,"generated":false,"vendored":false,"documentation":false,"detectable":true}"#
+        );
+    }
+}
@@ -1,16 +1,31 @@
 use super::GLOB_MATCH_OPTIONS;
 use glob::Pattern;
+use once_cell::sync::Lazy;
+use regex::RegexSet;
 use std::path::Path;
 
+/// The built-in patterns for vendored paths, which are the same as
+/// [Linguist's][vendor.yml], except for the ones that match first-party
+/// paths, like `dist/`, `.github/`, and `.d.ts` files. They are matched
+/// against the whole path.
+///
+/// [vendor.yml]: https://github.com/github-linguist/linguist/blob/master/lib/linguist/vendor.yml
+static PATTERNS: Lazy<RegexSet> = Lazy::new(|| RegexSet::new(Vendored::patterns()).unwrap());
+
+#[derive(Clone)]
 pub struct Vendored {
     globs: Vec<Pattern>,
+    patterns: RegexSet,
 }
 
 impl Vendored {
-    pub fn new() -> Self {
-        let globs = Self::globs();
-
-        Self { globs }
+    /// Creates a new vendored matcher. Paths matching `extra_globs` or
+    /// `extra_patterns` are also vendored.
+    pub fn new(extra_globs: Vec<Pattern>, extra_patterns: RegexSet) -> Self {
+        Self {
+            globs: extra_globs,
+            patterns: extra_patterns,
+        }
     }
 
     pub fn is_vendored<P: AsRef<Path>>(&self, filepath: P, contents: &[u8]) -> bool {
@@ -18,20 +33,206 @@ impl Vendored {
     }
 
     fn is_vendored_no_read<P: AsRef<Path>>(&self, filepath: P) -> bool {
+        let filepath = filepath.as_ref();
         self.globs
             .iter()
-            .any(|g| g.matches_path_with(filepath.as_ref(), GLOB_MATCH_OPTIONS))
+            .any(|g| g.matches_path_with(filepath, GLOB_MATCH_OPTIONS))
+            || filepath
+                .to_str()
+                .is_some_and(|path| self.patterns.is_match(path) || PATTERNS.is_match(path))
     }
 
     fn is_vendored_with_read<P: AsRef<Path>>(&self, _filepath: P, _contents: &[u8]) -> bool {
         false
     }
 
-    fn globs() -> Vec<Pattern> {
-        ["**/node_modules/**", "**/tests/fixtures/**"]
-            .into_iter()
-            .map(|g| Pattern::new(g).unwrap())
-            .collect()
+    fn patterns() -> &'static [&'static str] {
+        &[
+            // Dependencies
+            r"^[Dd]ependencies/",
+            // C dependencies
+            r"^deps/",
+            r"(^|/)configure$",
+            r"(^|/)config\.guess$",
+            r"(^|/)config\.sub$",
+            // Generated by autoconf
+            r"(^|/)aclocal\.m4",
+            r"(^|/)libtool\.m4",
+            r"(^|/)ltoptions\.m4",
+            r"(^|/)ltsugar\.m4",
+            r"(^|/)ltversion\.m4",
+            r"(^|/)lt~obsolete\.m4",
+            // .NET Core install scripts
+            r"(^|/)dotnet-install\.(ps1|sh)$",
+            // Linters
+            r"(^|/)cpplint\.py",
+            // Node dependencies
+            r"(^|/)node_modules/",
+            // Yarn 2
+            r"(^|/)\.yarn/releases/",
+            r"(^|/)\.yarn/plugins/",
+            r"(^|/)\.yarn/sdks/",
+            r"(^|/)\.yarn/versions/",
+            r"(^|/)\.yarn/unplugged/",
+            // esy.sh dependencies
+            r"(^|/)_esy$",
+            // Bower components
+            r"(^|/)bower_components/",
+            // Erlang bundles
+            r"^rebar$",
+            r"(^|/)erlang\.mk",
+            // Go dependencies and fixtures
+            r"(^|/)Godeps/_workspace/",
+            r"(^|/)testdata/",
+            // GNU indent profiles
+            r"(^|/)\.indent\.pro",
+            // Minified JavaScript and CSS
+            r"(\.|-)min\.(js|css)$",
+            // Stylesheets imported from packages
+            r"([^\s]*)import\.(css|less|scss|styl)$",
+            // CSS frameworks
+            r"(^|/)bootstrap([^/.]*)(\..*)?\.(js|css|less|scss|styl)$",
+            r"(^|/)custom\.bootstrap([^\s]*)(js|css|less|scss|styl)$",
+            r"(^|/)font-?awesome\.(css|less|scss|styl)$",
+            r"(^|/)font-?awesome/.*\.(css|less|scss|styl)$",
+            r"(^|/)foundation\.(css|less|scss|styl)$",
+            r"(^|/)normalize\.(css|less|scss|styl)$",
+            r"(^|/)skeleton\.(css|less|scss|styl)$",
+            r"(^|/)[Bb]ourbon/.*\.(css|less|scss|styl)$",
+            r"(^|/)animate\.(css|less|scss|styl)$",
+            r"(^|/)materialize\.(css|less|scss|styl|js)$",
+            r"(^|/)select2/.*\.(css|scss|js)$",
+            r"(^|/)bulma\.(css|sass|scss)$",
+            // Vendored dependencies
+            r"(3rd|[Tt]hird)[-_]?[Pp]arty/",
+            r"(^|/)vendors?/",
+            r"(^|/)[Ee]xtern(als?)?/",
+            r"(^|/)[Vv]+endor/",
+            // Debian packaging
+            r"^debian/",
+            // Haxelib bytecode
+            r"(^|/)run\.n$",
+            // Bootstrap Datepicker
+            r"(^|/)bootstrap-datepicker/",
+            // jQuery and its plugins
+            r"(^|/)jquery([^.]*)\.js$",
+            r"(^|/)jquery\-\d\.\d+(\.\d+)?\.js$",
+            r"(^|/)jquery\-ui(\-\d\.\d+(\.\d+)?)?(\.\w+)?\.(js|css)$",
+            r"(^|/)jquery\.(ui|effects)\.([^.]*)\.(js|css)$",
+            r"(^|/)jquery\.fn\.gantt\.js",
+            r"(^|/)jquery\.fancybox\.(js|css)",
+            r"(^|/)fuelux\.js",
+            r"(^|/)jquery\.fileupload(-\w+)?\.js$",
+            r"(^|/)jquery\.dataTables\.js",
+            r"(^|/)bootbox\.js",
+            r"(^|/)pdf\.worker\.js",
+            r"(^|/)slick\.\w+.js$",
+            // Leaflet plugins
+            r"(^|/)Leaflet\.Coordinates-\d+\.\d+\.\d+\.src\.js$",
+            r"(^|/)leaflet\.draw-src\.js",
+            r"(^|/)leaflet\.draw\.css",
+            r"(^|/)Control\.FullScreen\.css",
+            r"(^|/)Control\.FullScreen\.js",
+            r"(^|/)leaflet\.spin\.js",
+            r"(^|/)wicket-leaflet\.js",
+            // Editor workspace files
+            r"(^|/)\.sublime-project",
+            r"(^|/)\.sublime-workspace",
+            r"(^|/)\.vscode/",
+            // Prototype
+            r"(^|/)prototype(.*)\.js$",
+            r"(^|/)effects\.js$",
+            r"(^|/)controls\.js$",
+            r"(^|/)dragdrop\.js$",
+            // Other JavaScript libraries
+            r"(^|/)mootools([^.]*)\d+\.\d+.\d+([^.]*)\.js$",
+            r"(^|/)dojo\.js$",
+            r"(^|/)MochiKit\.js$",
+            r"(^|/)yahoo-([^.]*)\.js$",
+            r"(^|/)yui([^.]*)\.js$",
+            r"(^|/)ckeditor\.js$",
+            r"(^|/)tiny_mce([^.]*)\.js$",
+            r"(^|/)tiny_mce/(langs|plugins|themes|utils)",
+            r"(^|/)ace-builds/",
+            r"(^|/)fontello(.*?)\.css$",
+            r"(^|/)MathJax/",
+            r"(^|/)Chart\.js$",
+            r"(^|/)[Cc]ode[Mm]irror/(\d+\.\d+/)?(lib|mode|theme|addon|keymap|demo)",
+            r"(^|/)shBrush([^.]*)\.js$",
+            r"(^|/)shCore\.js$",
+            r"(^|/)shLegacy\.js$",
+            r"(^|/)angular([^.]*)\.js$",
+            r"(^|/)d3(\.v\d+)?([^.]*)\.js$",
+            r"(^|/)react(-[^.]*)?\.js$",
+            r"(^|/)flow-typed/.*\.js$",
+            r"(^|/)modernizr\-\d\.\d+(\.\d+)?\.js$",
+            r"(^|/)modernizr\.custom\.\d+\.js$",
+            r"(^|/)knockout-(\d+\.){3}(debug\.)?js$",
+            // Python
+            r"(^|/)docs?/_?(build|themes?|templates?|static)/",
+            r"(^|/)admin_media/",
+            r"(^|/)fabfile\.py$",
+            r"(^|/)waf$",
+            r"(^|/)\.osx$",
+            // Xcode and Apple frameworks
+            r"\.xctemplate/",
+            r"\.imageset/",
+            r"(^|/)Carthage/",
+            r"(^|/)Sparkle/",
+            r"(^|/)Crashlytics\.framework/",
+            r"(^|/)Fabric\.framework/",
+            r"(^|/)BuddyBuildSDK\.framework/",
+            r"(^|/)Realm\.framework",
+            r"(^|/)RealmSwift\.framework",
+            // Git configuration
+            r"(^|/)\.gitignore$",
+            r"(^|/)\.gitmodules$",
+            // Gradle and Maven wrappers
+            r"(^|/)gradlew$",
+            r"(^|/)gradlew\.bat$",
+            r"(^|/)gradle/wrapper/",
+            r"(^|/)mvnw$",
+            r"(^|/)mvnw\.cmd$",
+            r"(^|/)\.mvn/wrapper/",
+            // .NET
+            r"-vsdoc\.js$",
+            r"\.intellisense\.js$",
+            r"(^|/)jquery([^.]*)\.validate(\.unobtrusive)?\.js$",
+            r"(^|/)jquery([^.]*)\.unobtrusive\-ajax\.js$",
+            r"(^|/)[Mm]icrosoft([Mm]vc)?([Aa]jax|[Vv]alidation)(\.debug)?\.js$",
+            r"(^|/)[Pp]ackages/.+\.\d+/",
+            // ExtJS
+            r"(^|/)extjs/.*?\.(js|xml|txt|html|properties)$",
+            r"(^|/)extjs/(\.sencha|docs|builds|cmd|examples|locale|packages|plugins|resources|src|welcome)/",
+            r"(^|/)html5shiv\.js$",
+            // Test fixtures
+            r"(^|/)[Tt]ests?/fixtures/",
+            r"(^|/)[Ss]pecs?/fixtures/",
+            // PhoneGap and Cordova
+            r"(^|/)cordova([^.]*)\.js$",
+            r"(^|/)cordova\-\d\.\d(\.\d)?\.js$",
+            r"(^|/)foundation(\..*)?\.js$",
+            // Vagrant
+            r"(^|/)Vagrantfile$",
+            // macOS metadata
+            r"(^|/)\.[Dd][Ss]_[Ss]tore$",
+            // R packages
+            r"(^|/)inst/extdata/",
+            // Octicons
+            r"(^|/)octicons\.css",
+            // Typesafe Activator
+            r"(^|/)activator$",
+            r"(^|/)activator\.bat$",
+            // ProGuard
+            r"(^|/)proguard\.pro$",
+            r"(^|/)proguard-rules\.pro$",
+            // PuPHPet
+            r"(^|/)puphpet/",
+            // Android Google APIs
+            r"(^|/)\.google_apis/",
+            // Jenkins pipelines
+            r"(^|/)Jenkinsfile$",
+        ]
     }
 }
 
@@ -49,10 +250,47 @@ mod tests {
         case("", false),
         case("node_modules", false),
         case("tests/fixtures/foo.json", true),
-        case("package/tests/fixtures/foo.json", true)
+        case("package/tests/fixtures/foo.json", true),
+        case("static/js/jquery-3.7.1.min.js", true),
+        case("assets/bootstrap.css", true),
+        case("src/third-party/lib.c", true),
+        case("types/index.d.ts", false),
+        case(".github/workflows/ci.yml", false),
+        case("dist/app.js", false),
+        case("src/vendoring.rs", false)
     )]
     fn test_is_vendored_no_read(filepath: &str, expected: bool) {
-        let vendored = Vendored::new();
+        let vendored = Vendored::new(Vec::new(), RegexSet::empty());
+        assert_eq!(vendored.is_vendored_no_read(filepath), expected);
+    }
+
+    #[rstest(
+        filepath,
+        expected,
+        case("private/lib/lib.c", true),
+        case("src/private/lib.c", false),
+        case("src/generated/lib.rs", true),
+        case("src/lib.rs", false)
+    )]
+    fn test_is_vendored_extra_globs(filepath: &str, expected: bool) {
+        let globs = ["private/**", "**/generated/**"]
+            .into_iter()
+            .map(|g| Pattern::new(g).unwrap())
+            .collect();
+        let vendored = Vendored::new(globs, RegexSet::empty());
+        assert_eq!(vendored.is_vendored_no_read(filepath), expected);
+    }
+
+    #[rstest(
+        filepath,
+        expected,
+        case("src/lib.pb.rs", true),
+        case("proto/lib.pb.rs", true),
+        case("src/lib.rs", false)
+    )]
+    fn test_is_vendored_extra_patterns(filepath: &str, expected: bool) {
+        let patterns = RegexSet::new([r"\.pb\.rs$"]).unwrap();
+        let vendored = Vendored::new(Vec::new(), patterns);
         assert_eq!(vendored.is_vendored_no_read(filepath), expected);
     }
 }
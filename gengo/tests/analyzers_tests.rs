@@ -1,5 +1,8 @@
-use gengo::Analyzers;
+use gengo::languages::Category;
+use gengo::{Analyzers, Detection, Language, LanguageSet};
 use insta::assert_debug_snapshot;
+use rstest::rstest;
+use std::ffi::OsStr;
 
 mod util;
 
@@ -129,3 +132,356 @@ fn test_pick_filepath_pattern() {
         "It should prioritize filepath pattern over extension."
     );
 }
+
+#[test]
+fn test_pick_shebang_without_extension() {
+    let fixture = fixture_str!("test_simple-analyzers.yaml");
+    let analyzers = Analyzers::from_yaml(fixture).unwrap();
+    let language = analyzers
+        .pick("bin/script", b"#!/usr/bin/env fish\r\necho hi\r\n", 1 << 20)
+        .unwrap();
+    assert_eq!(language.name(), "Fish");
+}
+
+#[test]
+fn test_pick_shebang_longer_than_limit() {
+    let fixture = fixture_str!("test_simple-analyzers.yaml");
+    let analyzers = Analyzers::from_yaml(fixture).unwrap();
+    let contents = b"#!/usr/bin/env fish\necho hi\n";
+    assert!(
+        analyzers.pick("bin/script", contents, 8).is_none(),
+        "The shebang should not be read past the limit"
+    );
+    let language = analyzers.pick("script.sh", contents, 8).unwrap();
+    assert_eq!(
+        language.name(),
+        "Shell",
+        "It should fall back to the extension"
+    );
+}
+
+#[test]
+fn test_pick_binary() {
+    let fixture = fixture_str!("test_simple-analyzers.yaml");
+    let analyzers = Analyzers::from_yaml(fixture).unwrap();
+    let contents = b"#!/usr/bin/env fish\n\x00\x01\x02";
+    let language = analyzers.pick("script.sh", contents, 1 << 20).unwrap();
+    assert_eq!(
+        language.name(),
+        "Shell",
+        "The contents of binary files should not be read"
+    );
+    assert!(analyzers.pick("bin/script", contents, 1 << 20).is_none());
+}
+
+#[test]
+fn test_pick_modeline() {
+    let fixture = fixture_str!("test_check_json_with_comments-analyzers.yaml");
+    let analyzers = Analyzers::from_yaml(fixture).unwrap();
+    let contents = b"// vim: set ft=json:\n{\"msg\": \"hi\"}\n";
+    let language = analyzers.pick("test.json", contents, 1 << 20).unwrap();
+    assert_eq!(
+        language.name(),
+        "JSON",
+        "The modeline should take precedence over heuristics"
+    );
+
+    let contents = b"{\"msg\": \"hi\"}\n// vim: set ft=unknown:\n";
+    let language = analyzers.pick("test.json", contents, 1 << 20).unwrap();
+    assert_eq!(
+        language.name(),
+        "JSON with Comments",
+        "Unknown modes should be ignored"
+    );
+}
+
+#[test]
+fn test_language_set_takes_precedence() {
+    let fixture = fixture_str!("test_check_json_with_comments-analyzers.yaml");
+    let analyzers = Analyzers::from_yaml(fixture).unwrap();
+    let analyzers = LanguageSet::new()
+        .language(
+            "Config",
+            Category::Data,
+            "#123456",
+            &["json"],
+            &["devcontainer.json"],
+        )
+        .merge(analyzers)
+        .unwrap();
+    let language = analyzers.pick("test.json", b"// comment", 1 << 20).unwrap();
+    assert_eq!(language.name(), "Config");
+    let language = analyzers.pick("devcontainer.json", b"", 1 << 20).unwrap();
+    assert_eq!(language.name(), "Config");
+    let language = analyzers.pick("test.jsonc", b"", 1 << 20).unwrap();
+    assert_eq!(language.name(), "JSON with Comments");
+}
+
+#[test]
+fn test_language_set_rejects_duplicate_names() {
+    let languages = LanguageSet::new()
+        .language("Config", Category::Data, "#123456", &["cfg"], &[])
+        .language("config", Category::Data, "#654321", &["conf"], &[]);
+    assert!(languages.merge(Analyzers::default()).is_err());
+}
+
+#[test]
+fn test_pick_utf16() {
+    let fixture = fixture_str!("test_check_json_with_comments-analyzers.yaml");
+    let analyzers = Analyzers::from_yaml(fixture).unwrap();
+    let contents: Vec<u8> = [0xFF, 0xFE]
+        .into_iter()
+        .chain(
+            "// comment\n{}\n"
+                .encode_utf16()
+                .flat_map(|c| c.to_le_bytes()),
+        )
+        .collect();
+    let language = analyzers.pick("test.json", &contents, 1 << 20).unwrap();
+    assert_eq!(language.name(), "JSON with Comments");
+}
+
+#[test]
+fn test_pick_with_detection() {
+    let fixture = fixture_str!("test_check_json_with_comments-analyzers.yaml");
+    let analyzers = Analyzers::from_yaml(fixture).unwrap();
+    let detection = |filepath, contents: &[u8]| {
+        let (language, detection) = analyzers
+            .pick_with_detection(filepath, contents, 1 << 20)
+            .unwrap();
+        (language.name().to_string(), detection)
+    };
+    assert_eq!(
+        detection("test.jsonc", b"{}"),
+        ("JSON with Comments".into(), Detection::Extension)
+    );
+    assert_eq!(
+        detection("devcontainer.json", b"{}"),
+        ("JSON with Comments".into(), Detection::Filename)
+    );
+    assert_eq!(
+        detection("test.json", b"// comment\n{}"),
+        ("JSON with Comments".into(), Detection::Heuristic)
+    );
+    assert_eq!(
+        detection("test.json", b"{}\n// vim: ft=json\n"),
+        ("JSON".into(), Detection::Content)
+    );
+
+    let fixture = fixture_str!("test_simple-analyzers.yaml");
+    let analyzers = Analyzers::from_yaml(fixture).unwrap();
+    let (language, detection) = analyzers
+        .pick_with_detection("script", b"#!/usr/bin/env fish\n", 1 << 20)
+        .unwrap();
+    assert_eq!(language.name(), "Fish");
+    assert_eq!(detection, Detection::Shebang);
+}
+
+#[test]
+fn test_language_color() {
+    let analyzers = Analyzers::default();
+    let rust = analyzers.get("Rust").unwrap();
+    assert_eq!(rust.color(), "#DD3515");
+}
+
+#[test]
+fn test_all_languages() {
+    let analyzers = Analyzers::default();
+    assert_eq!(Language::all().count(), analyzers.languages().count());
+    let rust = Language::all().find(|l| l.name() == "Rust").unwrap();
+    assert_eq!(rust.category(), &Category::Programming);
+    assert_eq!(rust.color(), "#DD3515");
+}
+
+#[test]
+fn test_extensions() {
+    let analyzers = Analyzers::default();
+    let extensions: Vec<_> = analyzers.extensions("rust").collect();
+    assert_eq!(extensions, vec![OsStr::new("rs")]);
+    assert_eq!(analyzers.extensions("Not A Language").count(), 0);
+}
+
+#[test]
+fn test_language_from_name() {
+    let name = |name| Language::from_name(name).map(Language::name);
+    assert_eq!(name("Rust"), Some("Rust"));
+    assert_eq!(name("RUST"), Some("Rust"));
+    assert_eq!(name("plain-text"), Some("Plain Text"));
+    assert_eq!(name("Jinja-like"), Some("Jinja-like"));
+    assert_eq!(name("Not A Language"), None);
+}
+
+#[test]
+fn test_language_from_extension() {
+    let names = |extension| {
+        Language::from_extension(extension).map(|languages| {
+            languages
+                .into_iter()
+                .map(Language::name)
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(names("rs"), Some(vec!["Rust"]));
+    assert_eq!(names(".rs"), Some(vec!["Rust"]));
+    assert_eq!(names("h"), Some(vec!["C", "C++", "Objective-C"]));
+    assert_eq!(names("not-an-extension"), None);
+}
+
+#[rstest(
+    filepath,
+    contents,
+    expected,
+    detection,
+    case("lib.h", "int add(int a, int b);\n", "C", Detection::Extension),
+    case(
+        "lib.h",
+        "#include <vector>\nnamespace lib {\nstd::vector<int> v;\n}\n",
+        "C++",
+        Detection::Heuristic
+    ),
+    case(
+        "lib.h",
+        "#import <Foundation/Foundation.h>\n@interface Lib : NSObject\n@end\n",
+        "Objective-C",
+        Detection::Heuristic
+    ),
+    case("lib.m", "int x;\n", "Objective-C", Detection::Extension),
+    case(
+        "lib.m",
+        "#import \"Lib.h\"\n@implementation Lib\n@end\n",
+        "Objective-C",
+        Detection::Heuristic
+    ),
+    case(
+        "lib.m",
+        "% Adds two numbers.\nfunction c = add(a, b)\n  c = a + b;\nend\n",
+        "MATLAB",
+        Detection::Heuristic
+    ),
+    case(
+        "lib.m",
+        "(* Adds two numbers. *)\nadd[a_, b_] := a + b\n",
+        "Mathematica",
+        Detection::Heuristic
+    ),
+    case("lib.ts", "const x = 1;\n", "TypeScript", Detection::Extension),
+    case(
+        "lib.ts",
+        "import { x } from './x';\nexport const y = x;\n",
+        "TypeScript",
+        Detection::Heuristic
+    ),
+    case(
+        "lib.ts",
+        "<?xml version=\"1.0\"?>\n<TS version=\"2.1\" language=\"de_DE\">\n</TS>\n",
+        "XML",
+        Detection::Heuristic
+    )
+)]
+fn test_ambiguous_extensions(filepath: &str, contents: &str, expected: &str, detection: Detection) {
+    let analyzers = Analyzers::default();
+    let (language, actual) = analyzers
+        .pick_with_detection(filepath, contents.as_bytes(), 1 << 20)
+        .unwrap();
+    assert_eq!((language.name(), actual), (expected, detection));
+}
+
+#[rstest(
+    filepath,
+    expected,
+    case(".github/workflows/ci.yml", "GitHub Workflow"),
+    case("ci.yml", "YAML"),
+    case("config/workflows/ci.yml", "YAML"),
+    case("Dockerfile", "Docker"),
+    case("services/api/Dockerfile.dev", "Docker"),
+    case("packages/web/.env.local", ".Env"),
+    case("packages/web/tsconfig.build.json", "JSON with Comments"),
+    case("home/.ssh/config", "SSH Config")
+)]
+fn test_filepath_patterns(filepath: &str, expected: &str) {
+    let analyzers = Analyzers::default();
+    let language = analyzers.pick(filepath, b"", 1 << 20).unwrap();
+    assert_eq!(language.name(), expected);
+}
+
+#[rstest(
+    filepath,
+    expected,
+    case("Makefile", "Makefile"),
+    case("src/Makefile", "Makefile"),
+    case("Dockerfile", "Docker"),
+    case("Dockerfile.prod", "Docker"),
+    case("Rakefile", "Ruby"),
+    case("Gemfile", "Ruby"),
+    case("CMakeLists.txt", "CMake"),
+    case(".gitignore", "Ignore List")
+)]
+fn test_filenames(filepath: &str, expected: &str) {
+    let analyzers = Analyzers::default();
+    let (language, detection) = analyzers
+        .pick_with_detection(filepath, b"", 1 << 20)
+        .unwrap();
+    assert_eq!(
+        (language.name(), detection),
+        (expected, Detection::Filename)
+    );
+}
+
+#[test]
+fn test_popular_breaks_priority_ties() {
+    let fixture = fixture_str!("test_popular-analyzers.yaml");
+    let analyzers = Analyzers::from_yaml(fixture).unwrap();
+    let language = analyzers.pick("main.p", b"", 1 << 20).unwrap();
+    assert_eq!(language.name(), "Popular");
+    assert!(language.is_popular());
+    assert!(Language::from_name("Rust").unwrap().is_popular());
+    assert!(!Language::from_name("Plain Text").unwrap().is_popular());
+}
+
+#[rstest(
+    name,
+    expected,
+    case("c++", "C++"),
+    case("cpp", "C++"),
+    case("C#", "C#"),
+    case("csharp", "C#"),
+    case("Objective-C", "Objective-C"),
+    case("objc", "Objective-C"),
+    case("golang", "Go"),
+    case("JS", "JavaScript"),
+    case("ts", "TypeScript"),
+    case("Plain-Text", "Plain Text")
+)]
+fn test_resolve(name: &str, expected: &str) {
+    let analyzers = Analyzers::default();
+    assert_eq!(analyzers.resolve(name).unwrap().name(), expected);
+}
+
+#[test]
+fn test_resolve_unknown() {
+    let analyzers = Analyzers::default();
+    assert!(analyzers.resolve("javascrip").is_none());
+    assert!(analyzers.resolve("not-a-language").is_none());
+}
+
+#[test]
+fn test_suggest() {
+    let analyzers = Analyzers::default();
+    let suggest = |name| analyzers.suggest(name).map(|l| l.name().to_string());
+    assert_eq!(suggest("Pyton").as_deref(), Some("Python"));
+    assert_eq!(suggest("rust").as_deref(), Some("Rust"));
+    assert_eq!(suggest("Plain-Txt").as_deref(), Some("Plain Text"));
+    assert_eq!(suggest("Definitely Not A Language"), None);
+}
+
+#[test]
+fn test_category_all() {
+    let categories: Vec<_> = Category::all().collect();
+    assert_eq!(categories.len(), 5);
+    let detectable: Vec<_> = Category::all().filter(Category::is_detectable).collect();
+    assert_eq!(
+        detectable,
+        vec![Category::Markup, Category::Programming, Category::Query]
+    );
+    assert!(Language::all().all(|language| categories.contains(language.category())));
+}
@@ -0,0 +1,364 @@
+#[cfg(feature = "git")]
+use gengo::file_source::Git;
+use gengo::file_source::{FileSource, Fs, Memory, SymlinkPolicy, Tar};
+use gengo::Analyzers;
+use std::borrow::Cow;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+fn collect(source: &Fs) -> Vec<(PathBuf, Vec<u8>)> {
+    source
+        .files()
+        .unwrap()
+        .map(|(path, contents)| (path.into_owned(), contents.into_owned()))
+        .collect()
+}
+
+#[test]
+fn test_fs_walks_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), b"fn main() {}").unwrap();
+    fs::write(dir.path().join("README.md"), b"# Hello").unwrap();
+
+    let files = collect(&Fs::new(dir.path(), false));
+    assert_eq!(
+        files,
+        vec![
+            (PathBuf::from("README.md"), b"# Hello".to_vec()),
+            (
+                PathBuf::from("src").join("main.rs"),
+                b"fn main() {}".to_vec()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_fs_respects_gitignore() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("target")).unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join(".gitignore"), b"/target\n*.log\n").unwrap();
+    fs::write(dir.path().join("src/.gitignore"), b"generated.rs\n").unwrap();
+    fs::write(dir.path().join("target/out.rs"), b"").unwrap();
+    fs::write(dir.path().join("debug.log"), b"").unwrap();
+    fs::write(dir.path().join("src/generated.rs"), b"").unwrap();
+    fs::write(dir.path().join("src/main.rs"), b"").unwrap();
+
+    let paths: Vec<_> = collect(&Fs::new(dir.path(), true))
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from(".gitignore"),
+            PathBuf::from("src").join(".gitignore"),
+            PathBuf::from("src").join("main.rs"),
+        ]
+    );
+
+    let paths = collect(&Fs::new(dir.path(), false));
+    assert_eq!(paths.len(), 6, "Nothing should be ignored");
+}
+
+#[test]
+fn test_fs_respects_info_exclude() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join(".git/info")).unwrap();
+    fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+    fs::write(dir.path().join(".git/info/exclude"), b"*.tmp\nlocal.rs\n").unwrap();
+    fs::write(dir.path().join("src/nested/.gitignore"), b"!local.rs\n").unwrap();
+    fs::write(dir.path().join("scratch.tmp"), b"").unwrap();
+    fs::write(dir.path().join("src/local.rs"), b"").unwrap();
+    fs::write(dir.path().join("src/nested/local.rs"), b"").unwrap();
+
+    let source = Fs::new(dir.path(), true);
+    let paths: Vec<_> = collect(&source).into_iter().map(|(path, _)| path).collect();
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("src").join("nested").join(".gitignore"),
+            PathBuf::from("src").join("nested").join("local.rs"),
+        ],
+        "The nested .gitignore should take precedence over the excludes"
+    );
+
+    let paths = collect(&source.include_ignored(true));
+    assert_eq!(paths.len(), 4, "Nothing should be ignored");
+}
+
+#[test]
+#[cfg(unix)]
+fn test_fs_skips_symlink_loops() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("a")).unwrap();
+    fs::write(dir.path().join("a/file.txt"), b"hello").unwrap();
+    std::os::unix::fs::symlink(dir.path(), dir.path().join("a/loop")).unwrap();
+
+    let files = collect(&Fs::new(dir.path(), false));
+    assert_eq!(
+        files,
+        vec![(PathBuf::from("a").join("file.txt"), b"hello".to_vec())]
+    );
+}
+
+#[test]
+#[cfg(feature = "git")]
+fn test_git_reads_tree() {
+    let git = Git::new(env!("CARGO_MANIFEST_DIR"), "HEAD").unwrap();
+    let files: Vec<_> = git.files().unwrap().collect();
+    let (_, contents) = files
+        .iter()
+        .find(|(path, _)| path.as_ref() == std::path::Path::new("gengo/Cargo.toml"))
+        .expect("gengo/Cargo.toml should be in the tree");
+    assert!(contents.starts_with(b"[package]"));
+}
+
+#[test]
+fn test_memory_borrows_files() {
+    let memory = Memory::new(vec![
+        (PathBuf::from("Makefile"), b"all:\n\techo hello\n".to_vec()),
+        (PathBuf::from("src/main.rs"), b"fn main() {}".to_vec()),
+    ]);
+    let files: Vec<_> = memory.files().unwrap().collect();
+    assert!(files
+        .iter()
+        .all(|(path, contents)| matches!(path, Cow::Borrowed(_))
+            && matches!(contents, Cow::Borrowed(_))));
+
+    let analyzers = Analyzers::default();
+    let languages: Vec<_> = files
+        .iter()
+        .map(|(path, contents)| {
+            let language = analyzers.pick(path, contents, 1 << 20).unwrap();
+            (path.to_path_buf(), language.name().to_string())
+        })
+        .collect();
+    assert_eq!(
+        languages,
+        vec![
+            (PathBuf::from("Makefile"), "Makefile".to_string()),
+            (PathBuf::from("src/main.rs"), "Rust".to_string()),
+        ]
+    );
+}
+
+/// Creates a USTAR header for a member.
+fn tar_header(name: &str, prefix: &str, typeflag: u8, size: usize) -> Vec<u8> {
+    let mut header = vec![0u8; 512];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[124..135].copy_from_slice(format!("{size:011o}").as_bytes());
+    header[156] = typeflag;
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+    header
+}
+
+/// Appends a member and its padded contents to an archive.
+fn tar_member(archive: &mut Vec<u8>, name: &str, prefix: &str, typeflag: u8, contents: &[u8]) {
+    archive.extend(tar_header(name, prefix, typeflag, contents.len()));
+    archive.extend_from_slice(contents);
+    archive.resize(archive.len().div_ceil(512) * 512, 0);
+}
+
+fn collect_tar(tar: &Tar) -> Vec<(PathBuf, Vec<u8>)> {
+    tar.files()
+        .unwrap()
+        .map(|(path, contents)| (path.into_owned(), contents.into_owned()))
+        .collect()
+}
+
+#[test]
+fn test_tar_reads_regular_files() {
+    let long_name = format!("{}/lib.rs", "a".repeat(120));
+    let mut archive = Vec::new();
+    tar_member(&mut archive, "./src/", "", b'5', b"");
+    tar_member(&mut archive, "./src/main.rs", "", b'0', b"fn main() {}");
+    tar_member(&mut archive, "link.rs", "", b'2', b"");
+    tar_member(&mut archive, "README.md", "pkg-1.0", b'0', b"# Hello");
+    tar_member(
+        &mut archive,
+        "././@LongLink",
+        "",
+        b'L',
+        format!("{long_name}\0").as_bytes(),
+    );
+    // NOTE The header of a member with a long path holds the first 100
+    //      bytes of the path.
+    tar_member(&mut archive, &long_name[..100], "", b'0', b"pub fn f() {}");
+    archive.extend([0u8; 1024]);
+
+    let expected = vec![
+        (PathBuf::from("src/main.rs"), b"fn main() {}".to_vec()),
+        (PathBuf::from("pkg-1.0/README.md"), b"# Hello".to_vec()),
+        (PathBuf::from(long_name), b"pub fn f() {}".to_vec()),
+    ];
+    assert_eq!(collect_tar(&Tar::new(archive.clone()).unwrap()), expected);
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&archive).unwrap();
+    let compressed = encoder.finish().unwrap();
+    assert_eq!(collect_tar(&Tar::new(compressed).unwrap()), expected);
+}
+
+#[test]
+fn test_tar_reads_multiple_gzip_members() {
+    let mut first = Vec::new();
+    tar_member(&mut first, "a.rs", "", b'0', b"fn a() {}");
+    let mut second = Vec::new();
+    tar_member(&mut second, "b.rs", "", b'0', b"fn b() {}");
+    second.extend([0u8; 1024]);
+
+    let mut compressed = Vec::new();
+    for part in [first, second] {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&part).unwrap();
+        compressed.extend(encoder.finish().unwrap());
+    }
+    assert_eq!(
+        collect_tar(&Tar::new(compressed).unwrap()),
+        vec![
+            (PathBuf::from("a.rs"), b"fn a() {}".to_vec()),
+            (PathBuf::from("b.rs"), b"fn b() {}".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn test_tar_rejects_truncated_archive() {
+    let mut archive = Vec::new();
+    tar_member(&mut archive, "src/main.rs", "", b'0', b"fn main() {}");
+    tar_member(&mut archive, "src/lib.rs", "", b'0', &[b'a'; 600]);
+    archive.extend([0u8; 1024]);
+
+    for len in [520, 1024 + 100, 1024 + 512 + 100] {
+        let err = Tar::new(archive[..len].to_vec()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof, "{len}");
+    }
+    assert_eq!(collect_tar(&Tar::new(archive).unwrap()).len(), 2);
+}
+
+#[test]
+#[cfg(unix)]
+fn test_tar_reads_archive_from_tar_command() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("root");
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/main.rs"), b"fn main() {}").unwrap();
+    std::os::unix::fs::symlink("src/main.rs", root.join("main.rs")).unwrap();
+    let archive = dir.path().join("archive.tar.gz");
+    let status = std::process::Command::new("tar")
+        .arg("-czf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(&root)
+        .arg(".")
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let files = collect_tar(&Tar::open(&archive).unwrap());
+    assert_eq!(
+        files,
+        vec![(PathBuf::from("src/main.rs"), b"fn main() {}".to_vec())]
+    );
+}
+
+/// Creates a directory with a file, a link to it, a dangling link, and a
+/// link to a parent directory.
+#[cfg(unix)]
+fn symlinks_dir() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("a")).unwrap();
+    fs::write(dir.path().join("a/file.rs"), b"fn f() {}").unwrap();
+    std::os::unix::fs::symlink("file.rs", dir.path().join("a/link.rs")).unwrap();
+    std::os::unix::fs::symlink("missing.rs", dir.path().join("a/dangling.rs")).unwrap();
+    std::os::unix::fs::symlink("..", dir.path().join("a/parent")).unwrap();
+    dir
+}
+
+#[test]
+#[cfg(unix)]
+fn test_fs_symlink_policies() {
+    let dir = symlinks_dir();
+    let file = (PathBuf::from("a/file.rs"), b"fn f() {}".to_vec());
+
+    let files = collect(&Fs::new(dir.path(), false).symlinks(SymlinkPolicy::Skip));
+    assert_eq!(files, vec![file.clone()]);
+
+    let files = collect(&Fs::new(dir.path(), false).symlinks(SymlinkPolicy::FollowOnce));
+    assert_eq!(
+        files,
+        vec![
+            file.clone(),
+            (PathBuf::from("a/link.rs"), b"fn f() {}".to_vec())
+        ]
+    );
+
+    let files = collect(&Fs::new(dir.path(), false).symlinks(SymlinkPolicy::TreatAsFile));
+    assert_eq!(
+        files,
+        vec![
+            (PathBuf::from("a/dangling.rs"), b"missing.rs".to_vec()),
+            file,
+            (PathBuf::from("a/link.rs"), b"file.rs".to_vec()),
+            (PathBuf::from("a/parent"), b"..".to_vec()),
+        ]
+    );
+}
+
+#[test]
+#[cfg(feature = "git")]
+#[cfg(unix)]
+fn test_git_symlink_policies() {
+    let dir = symlinks_dir();
+    let repo = dir.path();
+    let git = |args: &[&str]| {
+        let status = std::process::Command::new("git")
+            .args([
+                "-c",
+                "user.name=gengo",
+                "-c",
+                "user.email=gengo@example.com",
+            ])
+            .args(args)
+            .current_dir(repo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "symlinks"]);
+
+    let collect = |policy| -> Vec<(PathBuf, Vec<u8>)> {
+        Git::new(repo, "HEAD")
+            .unwrap()
+            .symlinks(policy)
+            .files()
+            .unwrap()
+            .map(|(path, contents)| (path.into_owned(), contents.into_owned()))
+            .collect()
+    };
+    let file = (PathBuf::from("a/file.rs"), b"fn f() {}".to_vec());
+    assert_eq!(collect(SymlinkPolicy::Skip), vec![file.clone()]);
+    assert_eq!(
+        collect(SymlinkPolicy::FollowOnce),
+        vec![
+            file.clone(),
+            (PathBuf::from("a/link.rs"), b"fn f() {}".to_vec())
+        ]
+    );
+    assert_eq!(
+        collect(SymlinkPolicy::TreatAsFile),
+        vec![
+            (PathBuf::from("a/dangling.rs"), b"missing.rs".to_vec()),
+            file,
+            (PathBuf::from("a/link.rs"), b"file.rs".to_vec()),
+            (PathBuf::from("a/parent"), b"..".to_vec()),
+        ]
+    );
+}
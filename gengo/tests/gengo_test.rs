@@ -1,9 +1,44 @@
+use gengo::analysis::{SkipReason, SummaryOpts};
+#[cfg(feature = "git")]
+use gengo::file_source::FileSource;
+#[cfg(feature = "git")]
+use gengo::file_source::Git;
+use gengo::file_source::Memory;
+use gengo::languages::Category;
 use gengo::Analyzers;
+use gengo::BlobCache;
+use gengo::BlobReader;
 use gengo::Builder;
+use gengo::SubmodulePolicy;
+use gengo::{Assignment, AttributeState};
+use gengo::{Detection, Detector, ExtensionDetector, FullDetector, Language};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
 const ROOT: &str = env!("CARGO_MANIFEST_DIR");
 
 mod util;
 
+/// Runs a git command in `dir`, panicking if it fails.
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args([
+            "-c",
+            "user.name=gengo",
+            "-c",
+            "user.email=gengo@example.com",
+            "-c",
+            "protocol.file.allow=always",
+        ])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {:?} failed", args);
+}
+
 #[test]
 fn test_javascript() {
     let analyzers = fixture_str!("test_javascript-analyzers.yaml");
@@ -12,3 +47,2194 @@ fn test_javascript() {
     let results = gengo.analyze("test/javascript").unwrap();
     insta::assert_debug_snapshot!(results);
 }
+
+#[test]
+fn test_analyze_interrupted() {
+    let gengo = Builder::new(ROOT).build().unwrap();
+    let results = gengo
+        .analyze_with_interrupt("HEAD", &AtomicBool::new(true))
+        .unwrap();
+    assert_eq!(results.iter().count(), 0);
+}
+
+#[test]
+fn test_analyze_with_progress() {
+    let gengo = Builder::new(ROOT).build().unwrap();
+    let mut reported = Vec::new();
+    let results = gengo
+        .analyze_with_progress("HEAD", &AtomicBool::new(false), |processed, total| {
+            reported.push((processed, total))
+        })
+        .unwrap();
+    assert!(!reported.is_empty());
+    assert!(reported.windows(2).all(|w| w[0].0 < w[1].0));
+    let (processed, total) = *reported.last().unwrap();
+    assert_eq!(processed, total);
+    assert!(total >= results.iter().count());
+}
+
+/// Creates a repository in `dir` with `main.rs` and a submodule at
+/// `deps/sub` containing `lib.rs`. Returns the path of the repository.
+fn repo_with_submodule(dir: &Path) -> PathBuf {
+    let submodule = dir.join("submodule");
+    let repo = dir.join("repo");
+    fs::create_dir_all(&submodule).unwrap();
+    fs::create_dir_all(&repo).unwrap();
+
+    git(&submodule, &["init", "-q"]);
+    fs::write(submodule.join("lib.rs"), b"pub fn f() {}").unwrap();
+    git(&submodule, &["add", "."]);
+    git(&submodule, &["commit", "-q", "-m", "submodule"]);
+
+    git(&repo, &["init", "-q"]);
+    fs::write(repo.join("main.rs"), b"fn main() {}").unwrap();
+    git(
+        &repo,
+        &["submodule", "add", "-q", "../submodule", "deps/sub"],
+    );
+    git(&repo, &["add", "."]);
+    git(&repo, &["commit", "-q", "-m", "repo"]);
+    repo
+}
+
+/// Creates a repository with the submodule `sub`, which has the submodule
+/// `sub/nested`.
+fn repo_with_nested_submodule(dir: &Path) -> PathBuf {
+    let nested = dir.join("nested");
+    fs::create_dir_all(&nested).unwrap();
+    git(&nested, &["init", "-q"]);
+    fs::write(nested.join("nested.rs"), b"pub fn g() {}").unwrap();
+    git(&nested, &["add", "."]);
+    git(&nested, &["commit", "-q", "-m", "nested"]);
+
+    let submodule = dir.join("submodule");
+    fs::create_dir_all(&submodule).unwrap();
+    git(&submodule, &["init", "-q"]);
+    fs::write(submodule.join("lib.rs"), b"pub fn f() {}").unwrap();
+    git(
+        &submodule,
+        &["submodule", "add", "-q", "../nested", "nested"],
+    );
+    git(&submodule, &["add", "."]);
+    git(&submodule, &["commit", "-q", "-m", "submodule"]);
+
+    let repo = dir.join("repo");
+    fs::create_dir_all(&repo).unwrap();
+    git(&repo, &["init", "-q"]);
+    fs::write(repo.join("main.rs"), b"fn main() {}").unwrap();
+    git(&repo, &["submodule", "add", "-q", "../submodule", "sub"]);
+    git(
+        &repo,
+        &["submodule", "update", "-q", "--init", "--recursive"],
+    );
+    git(&repo, &["add", "."]);
+    git(&repo, &["commit", "-q", "-m", "repo"]);
+    repo
+}
+
+/// The Rust files of an analysis, and whether they are vendored.
+fn rust_files_vendored(gengo: &gengo::Gengo) -> Vec<(PathBuf, bool)> {
+    let results = gengo.analyze("HEAD").unwrap();
+    results
+        .iter()
+        .map(|(path, entry)| (path.into_owned(), entry.vendored()))
+        .filter(|(path, _)| path.extension().is_some_and(|ext| ext == "rs"))
+        .collect()
+}
+
+#[test]
+fn test_submodules() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = repo_with_submodule(dir.path());
+
+    let gengo = Builder::new(&repo).build().unwrap();
+    assert_eq!(
+        rust_files_vendored(&gengo),
+        vec![
+            (PathBuf::from("main.rs"), false),
+            (PathBuf::from("deps/sub/lib.rs"), true),
+        ]
+    );
+}
+
+#[test]
+fn test_submodule_policies() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = repo_with_submodule(dir.path());
+
+    let gengo = Builder::new(&repo)
+        .submodules(SubmodulePolicy::Recurse)
+        .build()
+        .unwrap();
+    assert_eq!(
+        rust_files_vendored(&gengo),
+        vec![
+            (PathBuf::from("main.rs"), false),
+            (PathBuf::from("deps/sub/lib.rs"), false),
+        ]
+    );
+
+    let gengo = Builder::new(&repo)
+        .submodules(SubmodulePolicy::Ignore)
+        .build()
+        .unwrap();
+    assert_eq!(
+        rust_files_vendored(&gengo),
+        vec![(PathBuf::from("main.rs"), false)]
+    );
+}
+
+#[test]
+fn test_duplicate_submodules_are_analyzed_once() {
+    struct Counting(Arc<AtomicUsize>);
+
+    impl Detector for Counting {
+        fn detect<'a>(
+            &self,
+            analyzers: &'a Analyzers,
+            filepath: &Path,
+            contents: &[u8],
+            limit: usize,
+        ) -> Option<(&'a Language, Detection)> {
+            if filepath == Path::new("lib.rs") {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+            FullDetector.detect(analyzers, filepath, contents, limit)
+        }
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let repo = repo_with_submodule(dir.path());
+    git(
+        &repo,
+        &["submodule", "add", "-q", "../submodule", "deps/other"],
+    );
+    git(&repo, &["commit", "-q", "-m", "other"]);
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let gengo = Builder::new(&repo)
+        .detector(Box::new(Counting(Arc::clone(&count))))
+        .build()
+        .unwrap();
+    assert_eq!(
+        rust_files_vendored(&gengo),
+        vec![
+            (PathBuf::from("main.rs"), false),
+            (PathBuf::from("deps/sub/lib.rs"), true),
+            (PathBuf::from("deps/other/lib.rs"), true),
+        ]
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    let streamed: Vec<_> = gengo
+        .analyze_streaming("HEAD")
+        .unwrap()
+        .map(|result| result.unwrap().0)
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+        .collect();
+    assert_eq!(
+        streamed,
+        vec![
+            PathBuf::from("main.rs"),
+            PathBuf::from("deps/sub/lib.rs"),
+            PathBuf::from("deps/other/lib.rs"),
+        ]
+    );
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_analyze_streaming() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = repo_with_submodule(dir.path());
+    let gengo = Builder::new(&repo).build().unwrap();
+
+    let streamed: Vec<_> = gengo
+        .analyze_streaming("HEAD")
+        .unwrap()
+        .map(|result| {
+            let (path, entry) = result.unwrap();
+            (path, entry.language().name().to_string(), entry.vendored())
+        })
+        .collect();
+    let results = gengo.analyze("HEAD").unwrap();
+    let analyzed: Vec<_> = results
+        .iter()
+        .map(|(path, entry)| {
+            (
+                path.into_owned(),
+                entry.language().name().to_string(),
+                entry.vendored(),
+            )
+        })
+        .collect();
+    assert_eq!(streamed, analyzed);
+    assert!(streamed.contains(&(PathBuf::from("deps/sub/lib.rs"), "Rust".to_string(), true)));
+
+    assert!(gengo.analyze_streaming("does-not-exist").is_err());
+
+    let repo = repo_with_nested_submodule(&dir.path().join("nested"));
+    let gengo = Builder::new(&repo).max_submodule_depth(1).build().unwrap();
+    let mut stream = gengo.analyze_streaming("HEAD").unwrap();
+    let streamed: Vec<_> = stream
+        .by_ref()
+        .map(|result| result.unwrap().0)
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+        .collect();
+    assert_eq!(
+        streamed,
+        vec![PathBuf::from("main.rs"), PathBuf::from("sub/lib.rs")]
+    );
+    let results = gengo.analyze("HEAD").unwrap();
+    assert_eq!(
+        stream.skipped(),
+        vec![("sub/nested".into(), SkipReason::MaxSubmoduleDepth)]
+    );
+    assert_eq!(stream.skipped(), results.skipped());
+    assert_eq!(stream.submodule_commits(), results.submodule_commits());
+}
+
+#[test]
+fn test_summary_include_unknown() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(repo.join("main.rs"), b"fn main() {}\n").unwrap();
+    fs::write(repo.join("notes.unknown"), b"some notes\n").unwrap();
+    fs::write(repo.join("data.unknown"), [0u8, 1, 2, 3, 0, 255]).unwrap();
+    fs::create_dir_all(repo.join("vendor")).unwrap();
+    fs::write(repo.join("vendor/notes.unknown"), b"vendored\n").unwrap();
+    fs::write(repo.join("vendor/data.unknown"), [0u8, 1]).unwrap();
+    fs::write(repo.join("kept.unknown"), b"generated\n").unwrap();
+    // NOTE The attributes aren't committed, so that they aren't analyzed.
+    fs::create_dir_all(repo.join(".git/info")).unwrap();
+    fs::write(
+        repo.join(".git/info/attributes"),
+        "*.unknown gengo-generated\nnotes.unknown -gengo-generated\ndata.unknown -gengo-generated\nkept.unknown gengo-detectable\n",
+    )
+    .unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "unknown"]);
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let sizes = |opts| {
+        results
+            .summary_with(opts)
+            .iter()
+            .map(|(language, size)| (language.name().to_string(), *size))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        sizes(SummaryOpts::default()),
+        vec![("Rust".to_string(), 13)]
+    );
+
+    let mut opts = SummaryOpts::default();
+    opts.include_unknown = true;
+    let mut actual = sizes(opts);
+    actual.sort();
+    assert_eq!(
+        actual,
+        vec![
+            ("Binary".to_string(), 6),
+            ("Rust".to_string(), 13),
+            ("Unknown".to_string(), 21),
+        ]
+    );
+
+    let mut opts = SummaryOpts::default();
+    opts.include_unknown = true;
+    opts.all = true;
+    let actual = sizes(opts);
+    assert!(actual.contains(&("Binary".to_string(), 8)), "{actual:?}");
+    assert!(actual.contains(&("Unknown".to_string(), 30)), "{actual:?}");
+}
+
+/// Resolves a revision to a full object id with git.
+fn rev_parse(dir: &Path, rev: &str) -> String {
+    let output = Command::new("git")
+        .args(["rev-parse", rev])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[test]
+fn test_analyzed_revision() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = repo_with_submodule(dir.path());
+    let gengo = Builder::new(&repo).build().unwrap();
+
+    let results = gengo.analyze("HEAD").unwrap();
+    assert_eq!(
+        results.revision().to_string(),
+        rev_parse(&repo, "HEAD^{tree}")
+    );
+    assert_eq!(
+        results.commit().map(|id| id.to_string()),
+        Some(rev_parse(&repo, "HEAD"))
+    );
+    let submodule_commits: Vec<_> = results
+        .submodule_commits()
+        .iter()
+        .map(|(path, id)| (path.to_string(), id.to_string()))
+        .collect();
+    assert_eq!(
+        submodule_commits,
+        vec![(
+            "deps/sub".to_string(),
+            rev_parse(&dir.path().join("submodule"), "HEAD")
+        )]
+    );
+
+    let tree = rev_parse(&repo, "HEAD^{tree}");
+    let results = gengo.analyze(&tree).unwrap();
+    assert_eq!(results.revision().to_string(), tree);
+    assert_eq!(results.commit(), None);
+}
+
+#[test]
+fn test_analyze_diff() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(repo.join("unchanged.rs"), b"fn a() {}").unwrap();
+    fs::write(repo.join("modified.rs"), b"fn b() {}").unwrap();
+    fs::write(repo.join("deleted.rs"), b"fn c() {}").unwrap();
+    fs::write(repo.join("renamed.rs"), b"fn d() {}").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "base"]);
+
+    fs::write(repo.join("modified.rs"), b"fn b() { todo!() }").unwrap();
+    fs::write(repo.join("added.rs"), b"fn e() {}").unwrap();
+    git(repo, &["rm", "-q", "deleted.rs"]);
+    git(repo, &["mv", "renamed.rs", "new-name.rs"]);
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "head"]);
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let results = gengo.analyze_diff("HEAD^", "HEAD").unwrap();
+    let paths: Vec<_> = results.iter().map(|(path, _)| path.into_owned()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("added.rs"),
+            PathBuf::from("modified.rs"),
+            PathBuf::from("new-name.rs"),
+        ]
+    );
+}
+
+#[test]
+fn test_attributes_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path().join("repo");
+    fs::create_dir_all(&repo).unwrap();
+    git(&repo, &["init", "-q"]);
+    fs::write(repo.join("main.rs"), b"fn main() {}").unwrap();
+    fs::write(repo.join("build.rs"), b"fn main() {}").unwrap();
+    git(&repo, &["add", "."]);
+    git(&repo, &["commit", "-q", "-m", "repo"]);
+    let attributes_file = dir.path().join("attributes");
+    fs::write(&attributes_file, b"build.rs gengo-generated\n").unwrap();
+
+    let gengo = Builder::new(&repo)
+        .attributes_file(&attributes_file)
+        .build()
+        .unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let mut entries: Vec<_> = results
+        .iter()
+        .map(|(path, entry)| (path.into_owned(), entry.generated()))
+        .collect();
+    entries.sort();
+    assert_eq!(
+        entries,
+        vec![("build.rs".into(), true), ("main.rs".into(), false)]
+    );
+
+    let gengo = Builder::new(&repo)
+        .attributes_file(dir.path().join("missing"))
+        .build();
+    assert!(gengo.is_ok(), "A missing attributes file should be ignored");
+}
+
+#[test]
+fn test_build_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let err = Builder::new(dir.path()).build().err().unwrap();
+    assert!(matches!(err.kind(), gengo::ErrorKind::NoRepository));
+    assert_eq!(
+        err.context(),
+        Some(dir.path().display().to_string().as_str())
+    );
+    #[cfg(feature = "git")]
+    {
+        let err = Git::new(dir.path(), "HEAD").err().unwrap();
+        assert!(matches!(err.kind(), gengo::ErrorKind::NoRepository));
+    }
+
+    let err = Builder::new(ROOT)
+        .generated_globs(["[invalid"])
+        .build()
+        .err()
+        .unwrap();
+    assert!(matches!(err.kind(), gengo::ErrorKind::InvalidGlob));
+    assert!(err.to_string().contains("[invalid"));
+}
+
+#[test]
+fn test_read_limit() {
+    let gengo = Builder::new(ROOT).build().unwrap();
+    assert_eq!(gengo.read_limit(), Builder::<&str>::DEFAULT_READ_LIMIT);
+
+    let dir = tempfile::tempdir().unwrap();
+    git(dir.path(), &["init", "-q"]);
+    let minified = format!("var a = [{}];\n", "1, ".repeat(100));
+    fs::write(dir.path().join("index.js"), minified).unwrap();
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-q", "-m", "minified"]);
+
+    let generated = |read_limit| {
+        let gengo = Builder::new(dir.path())
+            .read_limit(read_limit)
+            .build()
+            .unwrap();
+        assert_eq!(gengo.read_limit(), read_limit);
+        let results = gengo.analyze("HEAD").unwrap();
+        let (_, entry) = results.iter().next().unwrap();
+        entry.generated()
+    };
+    assert!(generated(1 << 20));
+    assert!(
+        !generated(100),
+        "Only the first 100 bytes should be read, which isn't a long line"
+    );
+}
+
+#[test]
+fn test_read_limit_size() {
+    let dir = tempfile::tempdir().unwrap();
+    git(dir.path(), &["init", "-q"]);
+    let contents = format!("fn main() {{}}\n{}// DO NOT EDIT\n", "\n".repeat(200));
+    fs::write(dir.path().join("main.rs"), &contents).unwrap();
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-q", "-m", "large"]);
+
+    for detector in [
+        Box::new(FullDetector) as Box<dyn Detector>,
+        Box::new(ExtensionDetector),
+    ] {
+        let gengo = Builder::new(dir.path())
+            .read_limit(100)
+            .detector(detector)
+            .build()
+            .unwrap();
+        let results = gengo.analyze("HEAD").unwrap();
+        let entry = results.get("main.rs").unwrap();
+        assert_eq!(entry.size(), contents.len());
+        assert!(
+            !entry.generated(),
+            "The signature is past the read limit, so it shouldn't be seen"
+        );
+    }
+}
+
+#[test]
+fn test_max_blob_size() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    let large = format!("#!/usr/bin/env python\n{}", "x = 1\n".repeat(100));
+    fs::write(repo.join("large"), &large).unwrap();
+    fs::write(repo.join("large.rs"), &large).unwrap();
+    fs::write(repo.join("small.rs"), b"fn main() {}\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "large"]);
+
+    let entries = |results: &gengo::Analysis| {
+        let mut entries: Vec<_> = results
+            .iter()
+            .map(|(path, entry)| {
+                let counts = (entry.size(), entry.lines());
+                (
+                    path.into_owned(),
+                    entry.language().name().to_string(),
+                    counts,
+                )
+            })
+            .collect();
+        entries.sort();
+        entries
+    };
+    let gengo = Builder::new(repo).max_blob_size(100).build().unwrap();
+    assert_eq!(
+        entries(&gengo.analyze("HEAD").unwrap()),
+        vec![
+            ("large".into(), "Python".into(), (large.len(), 0)),
+            ("large.rs".into(), "Python".into(), (large.len(), 0)),
+            ("small.rs".into(), "Rust".into(), (13, 1)),
+        ],
+        "The start of large loose blobs should still be read"
+    );
+
+    let shared = tempfile::tempdir().unwrap();
+    let shared = shared.path().join("shared");
+    git(
+        repo,
+        &["clone", "-q", "--shared", ".", shared.to_str().unwrap()],
+    );
+    let shared_gengo = Builder::new(&shared).max_blob_size(100).build().unwrap();
+    assert_eq!(
+        entries(&shared_gengo.analyze("HEAD").unwrap()),
+        entries(&gengo.analyze("HEAD").unwrap()),
+        "The start of large loose blobs in alternates should still be read"
+    );
+
+    git(repo, &["gc", "-q"]);
+    assert_eq!(
+        entries(&gengo.analyze("HEAD").unwrap()),
+        vec![
+            ("large.rs".into(), "Rust".into(), (large.len(), 0)),
+            ("small.rs".into(), "Rust".into(), (13, 1)),
+        ],
+        "Large packed blobs should only be identified by their path"
+    );
+
+    fs::write(repo.join("large"), large.replace("x = 1", "y = 2")).unwrap();
+    assert_eq!(
+        entries(&gengo.analyze_worktree().unwrap()),
+        vec![
+            ("large".into(), "Python".into(), (large.len(), 0)),
+            ("large.rs".into(), "Rust".into(), (large.len(), 0)),
+            ("small.rs".into(), "Rust".into(), (13, 1)),
+        ],
+        "The start of large files in the worktree should still be read"
+    );
+}
+
+#[test]
+fn test_summary_by_chars() {
+    let dir = tempfile::tempdir().unwrap();
+    git(dir.path(), &["init", "-q"]);
+    fs::write(dir.path().join("main.rs"), "// 日本語\nfn main() {}\n").unwrap();
+    fs::write(dir.path().join("data.bin"), b"\0\x01\x02\x03").unwrap();
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-q", "-m", "chars"]);
+
+    let gengo = Builder::new(dir.path()).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let entry = results.get("main.rs").unwrap();
+    assert_eq!(entry.size(), 26);
+    assert_eq!(entry.chars(), 20);
+
+    let mut opts = SummaryOpts::default();
+    opts.by_chars = true;
+    opts.include_unknown = true;
+    let summary = results.summary_with(opts);
+    let sizes: Vec<_> = summary
+        .iter()
+        .map(|(language, size)| (language.name(), *size))
+        .collect();
+    assert_eq!(sizes, vec![("Rust", 20), ("Binary", 0)]);
+}
+
+#[test]
+fn test_lfs_pointers() {
+    struct Fetch;
+
+    impl BlobReader for Fetch {
+        fn read(&self, _filepath: &Path, contents: &[u8]) -> Option<Vec<u8>> {
+            gengo::lfs::Pointer::parse(contents).map(|_| b"#!/bin/sh\necho hi\n".to_vec())
+        }
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    git(dir.path(), &["init", "-q"]);
+    let pointer = "version https://git-lfs.github.com/spec/v1\n\
+                   oid sha256:4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393\n\
+                   size 12345\n";
+    fs::write(dir.path().join("schema.sql"), pointer).unwrap();
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-q", "-m", "lfs"]);
+
+    let gengo = Builder::new(dir.path()).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let entry = results.get("schema.sql").unwrap();
+    assert_eq!(entry.language().name(), "SQL");
+    assert_eq!(entry.size(), 12345);
+    assert_eq!(entry.lines(), 0);
+
+    let gengo = Builder::new(dir.path())
+        .blob_reader(Box::new(Fetch))
+        .build()
+        .unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let entry = results.get("schema.sql").unwrap();
+    assert_eq!(entry.size(), 18);
+    assert_eq!(entry.lines(), 2);
+}
+
+#[test]
+fn test_binary_attribute() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(repo.join("data.json"), b"{}\n").unwrap();
+    fs::write(repo.join("notes.md"), b"# Notes\n").unwrap();
+    fs::write(repo.join("script"), b"#!/bin/sh\necho hi\n").unwrap();
+    fs::write(repo.join("main.rs"), b"fn main() {}\n").unwrap();
+    fs::write(
+        repo.join(".gitattributes"),
+        b"data.json binary\nnotes.md -text\nscript -text\nmain.rs text\n",
+    )
+    .unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "binary"]);
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let mut entries: Vec<_> = results
+        .iter()
+        .map(|(path, entry)| {
+            let flags = (entry.is_binary(), entry.detectable(), entry.lines());
+            (path.into_owned(), entry.language().name(), flags)
+        })
+        .collect();
+    entries.sort();
+    assert_eq!(
+        entries,
+        vec![
+            ("data.json".into(), "JSON", (true, false, 0)),
+            ("main.rs".into(), "Rust", (false, true, 1)),
+            ("notes.md".into(), "Markdown", (true, false, 0)),
+        ],
+        "Binary files should be identified by path, and the shebang shouldn't be read"
+    );
+}
+
+#[test]
+fn test_analyze_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let submodule = dir.path().join("submodule");
+    let repo = dir.path().join("repo");
+    fs::create_dir_all(submodule.join("src")).unwrap();
+    fs::create_dir_all(repo.join("src")).unwrap();
+    fs::create_dir_all(repo.join("srcs")).unwrap();
+
+    git(&submodule, &["init", "-q"]);
+    fs::write(submodule.join("lib.rs"), b"pub fn f() {}").unwrap();
+    fs::write(submodule.join("src/g.rs"), b"pub fn g() {}").unwrap();
+    git(&submodule, &["add", "."]);
+    git(&submodule, &["commit", "-q", "-m", "submodule"]);
+
+    git(&repo, &["init", "-q"]);
+    fs::write(repo.join("src/main.rs"), b"fn main() {}").unwrap();
+    fs::write(repo.join("srcs/other.rs"), b"fn other() {}").unwrap();
+    git(
+        &repo,
+        &["submodule", "add", "-q", "../submodule", "deps/sub"],
+    );
+    git(&repo, &["add", "."]);
+    git(&repo, &["commit", "-q", "-m", "repo"]);
+
+    let gengo = Builder::new(&repo).build().unwrap();
+    let paths = |dir: &str| {
+        let results = gengo.analyze_path("HEAD", dir).unwrap();
+        let mut paths: Vec<_> = results.iter().map(|(path, _)| path.into_owned()).collect();
+        paths.sort();
+        paths
+    };
+    assert_eq!(paths("src"), vec![PathBuf::from("src/main.rs")]);
+    assert_eq!(paths("src/"), vec![PathBuf::from("src/main.rs")]);
+    assert_eq!(
+        paths("deps"),
+        vec![
+            PathBuf::from("deps/sub/lib.rs"),
+            PathBuf::from("deps/sub/src/g.rs"),
+        ]
+    );
+    assert_eq!(
+        paths("deps/sub/src"),
+        vec![PathBuf::from("deps/sub/src/g.rs")]
+    );
+
+    let err = gengo.analyze_path("HEAD", "missing").unwrap_err();
+    let err = err.downcast_ref::<gengo::Error>().unwrap();
+    assert!(matches!(err.kind(), gengo::ErrorKind::NoPath));
+    assert_eq!(err.context(), Some("missing"));
+    assert_eq!(err.to_string(), "path not found in revision: missing");
+}
+
+#[test]
+fn test_lines() {
+    let dir = tempfile::tempdir().unwrap();
+    git(dir.path(), &["init", "-q"]);
+    git(dir.path(), &["config", "core.autocrlf", "false"]);
+    fs::write(dir.path().join("lf.rs"), b"fn a() {}\nfn b() {}\n").unwrap();
+    fs::write(dir.path().join("crlf.rs"), b"fn a() {}\r\nfn b() {}\r\n").unwrap();
+    fs::write(dir.path().join("no-newline.rs"), b"fn a() {}\nfn b() {}").unwrap();
+    git(dir.path(), &["add", "."]);
+    git(dir.path(), &["commit", "-q", "-m", "lines"]);
+
+    let gengo = Builder::new(dir.path()).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    assert!(results.iter().all(|(_, entry)| entry.lines() == 2));
+    let mut opts = SummaryOpts::default();
+    opts.by_lines = true;
+    assert_eq!(results.summary_with(opts).total(), 6);
+}
+
+#[test]
+fn test_explain() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::create_dir_all(repo.join("dist")).unwrap();
+    fs::write(repo.join("dist/main.rs"), b"fn main() {}").unwrap();
+    fs::write(repo.join(".gitattributes"), b"*.rs gengo-vendored\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "repo"]);
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let explanation = gengo.explain("HEAD", "dist/main.rs").unwrap();
+    let names = |languages: &[gengo::Language]| {
+        languages
+            .iter()
+            .map(|l| l.name().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(explanation.extension_matches()), vec!["Rust"]);
+    assert!(explanation.shebang_matches().is_empty());
+    assert!(explanation.filename_matches().is_empty());
+    assert_eq!(
+        explanation.attributes(),
+        &[("gengo-vendored".to_string(), "set".to_string())]
+    );
+    let entry = explanation.entry().unwrap();
+    assert_eq!(entry.language().name(), "Rust");
+    assert!(entry.generated());
+    assert!(entry.vendored());
+    assert!(!entry.detectable());
+
+    let err = gengo.explain("HEAD", "missing.rs").unwrap_err();
+    let err = err.downcast_ref::<gengo::Error>().unwrap();
+    assert_eq!(err.kind(), &gengo::ErrorKind::NoPath);
+    assert_eq!(err.context(), Some("missing.rs"));
+    assert!(gengo.explain("HEAD", "dist").is_err());
+}
+
+#[test]
+fn test_vendored_globs() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::create_dir_all(repo.join("third_party/lib")).unwrap();
+    fs::create_dir_all(repo.join("src")).unwrap();
+    fs::write(repo.join("third_party/lib/lib.rs"), b"pub fn f() {}").unwrap();
+    fs::write(repo.join("src/main.rs"), b"fn main() {}").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "repo"]);
+
+    let gengo = Builder::new(repo)
+        .vendored_globs(["third_party/**"])
+        .build()
+        .unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let mut entries: Vec<_> = results
+        .iter()
+        .map(|(path, entry)| (path.into_owned(), entry.vendored(), entry.detectable()))
+        .collect();
+    entries.sort();
+    assert_eq!(
+        entries,
+        vec![
+            ("src/main.rs".into(), false, true),
+            ("third_party/lib/lib.rs".into(), true, false),
+        ]
+    );
+
+    let gengo = Builder::new(repo).vendored_globs(["[invalid"]).build();
+    assert!(gengo.is_err());
+}
+
+#[test]
+fn test_generated_and_documentation_globs() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::create_dir_all(repo.join("manual")).unwrap();
+    fs::write(repo.join("manual/example.rs"), b"fn main() {}").unwrap();
+    fs::write(repo.join("schema.pb.rs"), b"pub struct Message;").unwrap();
+    fs::write(repo.join("attr.pb.rs"), b"pub struct Message;").unwrap();
+    fs::write(
+        repo.join(".gitattributes"),
+        b"attr.pb.rs -gengo-generated\n",
+    )
+    .unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "repo"]);
+
+    let gengo = Builder::new(repo)
+        .generated_globs(["**/*.pb.rs"])
+        .documentation_globs(["manual/**"])
+        .build()
+        .unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let mut entries: Vec<_> = results
+        .iter()
+        .map(|(path, entry)| (path.into_owned(), entry.generated(), entry.documentation()))
+        .collect();
+    entries.sort();
+    assert_eq!(
+        entries,
+        vec![
+            ("attr.pb.rs".into(), false, false),
+            ("manual/example.rs".into(), false, true),
+            ("schema.pb.rs".into(), true, false),
+        ],
+        "The attribute should take precedence over the glob"
+    );
+}
+
+#[test]
+fn test_generated_signatures() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(
+        repo.join("api.go"),
+        b"// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n",
+    )
+    .unwrap();
+    fs::write(
+        repo.join("schema.rs"),
+        b"// Generated by schemagen\npub struct Schema;\n",
+    )
+    .unwrap();
+    fs::write(repo.join("main.rs"), b"fn main() {}\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "repo"]);
+
+    let gengo = Builder::new(repo)
+        .generated_signatures(["Generated by schemagen"])
+        .build()
+        .unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let mut entries: Vec<_> = results
+        .iter()
+        .map(|(path, entry)| (path.into_owned(), entry.generated()))
+        .collect();
+    entries.sort();
+    assert_eq!(
+        entries,
+        vec![
+            ("api.go".into(), true),
+            ("main.rs".into(), false),
+            ("schema.rs".into(), true),
+        ]
+    );
+}
+
+#[test]
+fn test_exclude_globs() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::create_dir_all(repo.join("node_modules/lib")).unwrap();
+    fs::write(
+        repo.join("node_modules/lib/index.js"),
+        b"module.exports = 1;\n",
+    )
+    .unwrap();
+    fs::write(repo.join("main.rs"), b"fn main() {}\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "repo"]);
+
+    let gengo = Builder::new(repo)
+        .exclude_globs(["node_modules/**"])
+        .build()
+        .unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let paths: Vec<_> = results.iter().map(|(path, _)| path.into_owned()).collect();
+    assert_eq!(paths, vec![PathBuf::from("main.rs")]);
+    assert!(results.skipped().is_empty());
+
+    let gengo = Builder::new(repo).exclude_globs(["[invalid"]).build();
+    assert!(gengo.is_err());
+}
+
+#[test]
+fn test_only_extensions() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::create_dir_all(repo.join("src.d")).unwrap();
+    fs::write(repo.join("src.d/main.rs"), b"fn main() {}\n").unwrap();
+    fs::write(repo.join("Cargo.toml"), b"[package]\n").unwrap();
+    fs::write(repo.join("index.js"), b"f();\n").unwrap();
+    fs::write(repo.join("Makefile"), b"all:\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "repo"]);
+
+    let gengo = Builder::new(repo)
+        .only_extensions(["rs", "TOML"])
+        .build()
+        .unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let mut paths: Vec<_> = results.iter().map(|(path, _)| path.into_owned()).collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![PathBuf::from("Cargo.toml"), PathBuf::from("src.d/main.rs")]
+    );
+    assert!(results.skipped().is_empty());
+}
+
+#[test]
+fn test_category_overrides() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(repo.join("data.json"), b"{}\n").unwrap();
+    fs::write(repo.join("config.yaml"), b"a: 1\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "repo"]);
+
+    let json = Language::from_name("JSON").unwrap().clone();
+    let gengo = Builder::new(repo)
+        .category_overrides([(json, Category::Programming)])
+        .build()
+        .unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let json = results.get("data.json").unwrap();
+    assert!(json.detectable());
+    assert_eq!(json.language().category(), &Category::Data);
+    assert!(!results.get("config.yaml").unwrap().detectable());
+}
+
+#[test]
+fn test_detectable_categories() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(repo.join("guide.md"), b"# Guide\n").unwrap();
+    fs::write(repo.join("notes.md"), b"# Notes\n").unwrap();
+    fs::write(repo.join("main.rs"), b"fn main() {}\n").unwrap();
+    fs::write(repo.join(".gitattributes"), b"notes.md -gengo-detectable\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "repo"]);
+
+    let detectable = |gengo: gengo::Gengo| {
+        let results = gengo.analyze("HEAD").unwrap();
+        let mut paths: Vec<_> = results
+            .iter()
+            .filter(|(_, entry)| entry.detectable())
+            .map(|(path, _)| path.into_owned())
+            .collect();
+        paths.sort();
+        paths
+    };
+    let gengo = Builder::new(repo).build().unwrap();
+    assert_eq!(detectable(gengo), vec![PathBuf::from("main.rs")]);
+
+    let gengo = Builder::new(repo)
+        .detectable_categories([Category::Prose])
+        .build()
+        .unwrap();
+    assert_eq!(
+        detectable(gengo),
+        vec![PathBuf::from("guide.md")],
+        "The attribute should take precedence over the categories"
+    );
+}
+
+#[test]
+fn test_repository_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::create_dir_all(repo.join("bundled")).unwrap();
+    fs::write(repo.join("bundled/lib.rs"), b"fn lib() {}\n").unwrap();
+    fs::write(repo.join("bundled/kept.rs"), b"fn kept() {}\n").unwrap();
+    fs::write(repo.join("main.mydsl"), b"print 1\n").unwrap();
+    fs::write(
+        repo.join(".gengo.toml"),
+        concat!(
+            "vendored = [\"bundled/**\"]\n",
+            "\n",
+            "[languages.\"My DSL\"]\n",
+            "category = \"programming\"\n",
+            "color = \"#123456\"\n",
+            "extensions = [\"mydsl\"]\n",
+        ),
+    )
+    .unwrap();
+    fs::write(
+        repo.join(".gitattributes"),
+        b"bundled/kept.rs -gengo-vendored\n",
+    )
+    .unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "repo"]);
+
+    let gengo = Builder::new(repo).repository_config(true).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let mut entries: Vec<_> = results
+        .iter()
+        .map(|(path, entry)| (path.into_owned(), entry.language().name(), entry.vendored()))
+        .collect();
+    entries.sort();
+    assert_eq!(
+        entries,
+        vec![
+            (".gengo.toml".into(), "TOML", false),
+            ("bundled/kept.rs".into(), "Rust", false),
+            ("bundled/lib.rs".into(), "Rust", true),
+            ("main.mydsl".into(), "My DSL", false),
+        ],
+        "Attributes should take precedence over the configured globs"
+    );
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    assert!(
+        results.iter().all(|(_, entry)| !entry.vendored()),
+        "The configuration should only be read when enabled"
+    );
+
+    fs::write(repo.join(".gengo.toml"), b"vendored = \"bundled/**\"\n").unwrap();
+    let gengo = Builder::new(repo).repository_config(true).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    assert!(
+        results.get("bundled/lib.rs").unwrap().vendored(),
+        "The configuration should be read from the analyzed revision"
+    );
+
+    git(repo, &["commit", "-q", "-a", "-m", "invalid"]);
+    assert!(gengo.analyze("HEAD").is_err());
+    let results = gengo.analyze("HEAD~1").unwrap();
+    assert!(results.get("bundled/lib.rs").unwrap().vendored());
+}
+
+#[test]
+fn test_analyze_worktree() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(repo.join("main.rs"), b"fn main() {}\n").unwrap();
+    fs::write(repo.join("lib.py"), b"x = 1\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "repo"]);
+    fs::write(repo.join("staged.py"), b"y = 2\n").unwrap();
+    git(repo, &["add", "staged.py"]);
+    fs::write(repo.join("lib.py"), b"x = 1\ny = 2\nz = 3\n").unwrap();
+    fs::write(repo.join("untracked.py"), b"z = 3\n").unwrap();
+    fs::write(
+        repo.join(".gitattributes"),
+        b"main.rs gengo-language=Python\n",
+    )
+    .unwrap();
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let results = gengo.analyze_worktree().unwrap();
+    let mut entries: Vec<_> = results
+        .iter()
+        .map(|(path, entry)| (path.into_owned(), entry.language().name(), entry.lines()))
+        .collect();
+    entries.sort();
+    assert_eq!(
+        entries,
+        vec![
+            ("lib.py".into(), "Python", 3),
+            ("main.rs".into(), "Python", 1),
+            ("staged.py".into(), "Python", 1),
+        ],
+        "Modified files and attributes should be read from the worktree"
+    );
+    assert!(results.revision().is_null());
+}
+
+#[test]
+fn test_analyze_worktree_untracked() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(repo.join("main.rs"), b"fn main() {}\n").unwrap();
+    fs::write(repo.join(".gitignore"), b"target/\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "repo"]);
+    fs::create_dir_all(repo.join("src")).unwrap();
+    fs::write(repo.join("src/lib.py"), b"x = 1\ny = 2\n").unwrap();
+    fs::create_dir_all(repo.join("target")).unwrap();
+    fs::write(repo.join("target/out.rs"), b"fn out() {}\n").unwrap();
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let results = gengo.analyze_worktree().unwrap();
+    assert!(results.get("src/lib.py").is_none());
+
+    let gengo = Builder::new(repo).untracked(true).build().unwrap();
+    let results = gengo.analyze_worktree().unwrap();
+    let mut entries: Vec<_> = results
+        .iter()
+        .map(|(path, entry)| {
+            (
+                path.into_owned(),
+                entry.language().name(),
+                entry.lines(),
+                entry.is_untracked(),
+            )
+        })
+        .collect();
+    entries.sort();
+    assert_eq!(
+        entries,
+        vec![
+            (".gitignore".into(), "Ignore List", 1, false),
+            ("main.rs".into(), "Rust", 1, false),
+            ("src/lib.py".into(), "Python", 2, true),
+        ],
+        "Untracked files should be analyzed unless they are ignored"
+    );
+}
+
+#[test]
+#[cfg(feature = "wasm")]
+fn test_analyze_files() {
+    let files = [
+        ("src/main.rs".to_string(), b"fn main() {}\n".to_vec()),
+        ("bin/run".to_string(), b"#!/usr/bin/env python\n".to_vec()),
+        ("Makefile".to_string(), b"all:\n".to_vec()),
+        ("dist/app.js".to_string(), b"var x = 1;\n".to_vec()),
+    ];
+    let results = gengo::analyze_files(&files);
+    let mut entries: Vec<_> = results
+        .iter()
+        .map(|(path, entry)| {
+            (
+                path.into_owned(),
+                entry.language().name(),
+                entry.detection_source(),
+                entry.detectable(),
+            )
+        })
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        entries,
+        vec![
+            ("Makefile".into(), "Makefile", Detection::Filename, true),
+            ("bin/run".into(), "Python", Detection::Shebang, true),
+            (
+                "dist/app.js".into(),
+                "JavaScript",
+                Detection::Extension,
+                false
+            ),
+            ("src/main.rs".into(), "Rust", Detection::Extension, true),
+        ]
+    );
+}
+
+#[test]
+fn test_build_without_repository() {
+    let dir = tempfile::tempdir().unwrap();
+    let gengo = Builder::new(dir.path())
+        .exclude_globs(["*.md"])
+        .build_without_repository()
+        .unwrap();
+    let err = gengo.analyze("HEAD").unwrap_err();
+    let err = err.downcast_ref::<gengo::Error>().unwrap();
+    assert_eq!(err.kind(), &gengo::ErrorKind::NoRepository);
+
+    let mut memory = Memory::default();
+    memory.push("main.rs", b"fn main() {}\n".to_vec());
+    memory.push("README.md", b"# Readme\n".to_vec());
+    let results = gengo.analyze_source(&memory).unwrap();
+    assert_eq!(results.iter().count(), 1);
+    assert!(results.get("main.rs").is_some());
+}
+
+#[test]
+fn test_linked_worktree() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path().join("repo");
+    let worktree = dir.path().join("worktree");
+    fs::create_dir(&repo).unwrap();
+    git(&repo, &["init", "-q", "-b", "main"]);
+    fs::write(repo.join("main.rs"), b"fn main() {}\n").unwrap();
+    git(&repo, &["add", "."]);
+    git(&repo, &["commit", "-q", "-m", "main"]);
+    git(&repo, &["checkout", "-q", "-b", "other"]);
+    git(&repo, &["rm", "-q", "main.rs"]);
+    fs::write(repo.join("lib.py"), b"x = 1\n").unwrap();
+    git(&repo, &["add", "."]);
+    git(&repo, &["commit", "-q", "-m", "other"]);
+    git(&repo, &["checkout", "-q", "main"]);
+    git(
+        &repo,
+        &["worktree", "add", "-q", worktree.to_str().unwrap(), "other"],
+    );
+    fs::write(worktree.join("lib.py"), b"x = 1\ny = 2\n").unwrap();
+
+    let gengo = Builder::new(&worktree).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let paths: Vec<_> = results.iter().map(|(path, _)| path.into_owned()).collect();
+    assert_eq!(
+        paths,
+        vec![PathBuf::from("lib.py")],
+        "HEAD should be the head of the linked worktree"
+    );
+    let results = gengo.analyze_worktree().unwrap();
+    assert_eq!(results.get("lib.py").unwrap().lines(), 2);
+    assert!(results.get("main.rs").is_none());
+
+    let gengo = Builder::new(&repo).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    assert!(results.get("main.rs").is_some());
+}
+
+#[test]
+fn test_analyze_source() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(repo.join(".gitattributes"), b"*.rs gengo-language=Python\n").unwrap();
+
+    let mut memory = Memory::default();
+    memory.push("src/main.rs", b"fn main() {}\n".to_vec());
+    memory.push("node_modules/lib.js", b"var x = 1;\n".to_vec());
+    memory.push("unknown", b"???\n".to_vec());
+    memory.push("skipped.py", b"x = 1\n".to_vec());
+
+    let gengo = Builder::new(repo).exclude_globs(["*.py"]).build().unwrap();
+    let results = gengo.analyze_source(&memory).unwrap();
+    let mut entries: Vec<_> = results
+        .iter()
+        .map(|(path, entry)| {
+            (
+                path.into_owned(),
+                entry.language().name(),
+                entry.detectable(),
+            )
+        })
+        .collect();
+    entries.sort();
+    assert_eq!(
+        entries,
+        vec![
+            ("node_modules/lib.js".into(), "JavaScript", false),
+            ("src/main.rs".into(), "Rust", true),
+        ],
+        "Attributes shouldn't apply to other sources"
+    );
+    assert!(results.get("unknown").is_none());
+    assert!(results.get("skipped.py").is_none());
+    assert!(results.revision().is_null());
+}
+
+#[test]
+fn test_executable() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    for path in ["run", "script", "main.rb"] {
+        fs::write(repo.join(path), b"#!/usr/bin/env python\nprint(1)\n").unwrap();
+    }
+    git(repo, &["add", "."]);
+    git(repo, &["update-index", "--chmod=+x", "run", "main.rb"]);
+    git(repo, &["commit", "-q", "-m", "repo"]);
+
+    let gengo = Builder::new(repo)
+        .detector(Box::new(ExtensionDetector))
+        .build()
+        .unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let run = results.get("run").unwrap();
+    assert_eq!(
+        (run.language().name(), run.detection_source()),
+        ("Python", Detection::Shebang)
+    );
+    assert!(run.is_executable());
+    assert_eq!(
+        results.get("main.rb").unwrap().language().name(),
+        "Python",
+        "the shebang of an executable should take precedence"
+    );
+    assert!(
+        results.get("script").is_none(),
+        "only executables should be read for a shebang"
+    );
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    assert!(results.get("run").unwrap().is_executable());
+    assert!(!results.get("script").unwrap().is_executable());
+}
+
+#[test]
+fn test_embedded_languages() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(
+        repo.join("App.vue"),
+        b"<template><p></p></template>\n<script lang=\"ts\">\n</script>\n",
+    )
+    .unwrap();
+    fs::write(repo.join("README.md"), b"# Usage\n\n```sh\nmake\n```\n").unwrap();
+    fs::write(repo.join("main.rs"), b"fn main() {}\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "repo"]);
+
+    let embedded = |enabled: bool| {
+        let gengo = Builder::new(repo)
+            .embedded_languages(enabled)
+            .build()
+            .unwrap();
+        let results = gengo.analyze("HEAD").unwrap();
+        let mut entries: Vec<_> = results
+            .iter()
+            .map(|(path, entry)| {
+                let names: Vec<_> = entry
+                    .embedded_languages()
+                    .iter()
+                    .map(|language| language.name().to_string())
+                    .collect();
+                (
+                    path.into_owned(),
+                    entry.language().name().to_string(),
+                    names,
+                )
+            })
+            .collect();
+        entries.sort();
+        entries
+    };
+    assert!(embedded(false).iter().all(|(_, _, names)| names.is_empty()));
+    assert_eq!(
+        embedded(true),
+        vec![
+            (
+                "App.vue".into(),
+                "Vue".into(),
+                vec!["HTML".into(), "TypeScript".into()]
+            ),
+            ("README.md".into(), "Markdown".into(), vec!["Shell".into()]),
+            ("main.rs".into(), "Rust".into(), vec![]),
+        ]
+    );
+}
+
+#[test]
+fn test_detector() {
+    struct PlainText;
+
+    impl Detector for PlainText {
+        fn detect<'a>(
+            &self,
+            analyzers: &'a Analyzers,
+            _filepath: &Path,
+            _contents: &[u8],
+            _limit: usize,
+        ) -> Option<(&'a Language, Detection)> {
+            analyzers
+                .get("Plain Text")
+                .map(|language| (language, Detection::Extension))
+        }
+    }
+
+    let detectors: [Box<dyn Detector>; 2] = [Box::new(FullDetector), Box::new(PlainText)];
+    let names: Vec<_> = detectors
+        .into_iter()
+        .map(|detector| {
+            let gengo = Builder::new(ROOT).detector(detector).build().unwrap();
+            let results = gengo.analyze("HEAD").unwrap();
+            let (_, entry) = results
+                .iter()
+                .find(|(path, _)| path == Path::new("gengo/src/lib.rs"))
+                .unwrap();
+            entry.language().name().to_string()
+        })
+        .collect();
+    assert_eq!(names, vec!["Rust", "Plain Text"]);
+}
+
+#[test]
+fn test_extension_detector() {
+    let gengo = Builder::new(ROOT)
+        .detector(Box::new(ExtensionDetector))
+        .build()
+        .unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let (_, entry) = results
+        .iter()
+        .find(|(path, _)| path == Path::new("gengo/src/lib.rs"))
+        .unwrap();
+    assert_eq!(entry.language().name(), "Rust");
+    assert_eq!(entry.detection_source(), Detection::Extension);
+    assert!(
+        entry.size() > 0,
+        "The size should be read without the contents"
+    );
+}
+
+#[test]
+fn test_parallel_submodules() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path().join("repo");
+    fs::create_dir_all(&repo).unwrap();
+    git(&repo, &["init", "-q"]);
+    fs::write(repo.join("main.rs"), b"fn main() {}").unwrap();
+    for name in ["a", "b", "c"] {
+        let submodule = dir.path().join(name);
+        fs::create_dir_all(&submodule).unwrap();
+        git(&submodule, &["init", "-q"]);
+        fs::write(submodule.join("lib.rs"), b"pub fn f() {}").unwrap();
+        git(&submodule, &["add", "."]);
+        git(&submodule, &["commit", "-q", "-m", name]);
+        let path = format!("deps/{name}");
+        git(
+            &repo,
+            &["submodule", "add", "-q", &format!("../{name}"), &path],
+        );
+    }
+    git(&repo, &["add", "."]);
+    git(&repo, &["commit", "-q", "-m", "repo"]);
+
+    let paths = |threads| {
+        let gengo = Builder::new(&repo).threads(threads).build().unwrap();
+        let results = gengo.analyze("HEAD").unwrap();
+        let mut paths: Vec<_> = results.iter().map(|(path, _)| path.into_owned()).collect();
+        paths.sort();
+        paths
+    };
+    let expected: Vec<PathBuf> = vec![
+        "deps/a/lib.rs".into(),
+        "deps/b/lib.rs".into(),
+        "deps/c/lib.rs".into(),
+        "main.rs".into(),
+    ];
+    assert_eq!(paths(1), expected);
+    assert_eq!(paths(2), expected);
+    assert_eq!(paths(0), expected);
+}
+
+#[test]
+fn test_single_thread() {
+    let entries = |gengo: gengo::Gengo| {
+        let results = gengo.analyze("HEAD").unwrap();
+        let mut entries: Vec<_> = results
+            .iter()
+            .map(|(path, entry)| (path.into_owned(), entry.language().name().to_string()))
+            .collect();
+        entries.sort();
+        entries
+    };
+    let sequential = entries(Builder::new(ROOT).threads(1).build().unwrap());
+    let parallel = entries(Builder::new(ROOT).build().unwrap());
+    assert!(!sequential.is_empty());
+    assert_eq!(sequential, parallel);
+}
+
+#[test]
+fn test_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(repo.join("a.rs"), b"fn a() {}\n").unwrap();
+    fs::write(repo.join("b.rs"), b"fn b() {}\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "first"]);
+    fs::write(repo.join("b.rs"), b"fn b() {}\nfn c() {}\n").unwrap();
+    git(repo, &["commit", "-q", "-am", "second"]);
+    fs::write(repo.join(".gitattributes"), b"a.rs gengo-vendored\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "third"]);
+
+    let entries = |gengo: &gengo::Gengo, rev: &str| {
+        let results = gengo.analyze(rev).unwrap();
+        let mut entries: Vec<_> = results
+            .iter()
+            .map(|(path, entry)| (path.into_owned(), entry.lines(), entry.vendored()))
+            .collect();
+        entries.sort();
+        entries
+    };
+    let cache = Arc::new(BlobCache::new());
+    let cached = Builder::new(repo)
+        .cache(Arc::clone(&cache))
+        .build()
+        .unwrap();
+    let uncached = Builder::new(repo).build().unwrap();
+
+    assert_eq!(entries(&cached, "HEAD~2"), entries(&uncached, "HEAD~2"));
+    assert_eq!(cache.len(), 2);
+    assert_eq!(entries(&cached, "HEAD~1"), entries(&uncached, "HEAD~1"));
+    assert_eq!(cache.len(), 3, "only the changed blob should be added");
+
+    let entries = entries(&cached, "HEAD");
+    assert!(entries.contains(&(PathBuf::from("a.rs"), 1, true)));
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_cache_shared_between_repositories() {
+    struct Counting(Arc<AtomicUsize>);
+
+    impl Detector for Counting {
+        fn detect<'a>(
+            &self,
+            analyzers: &'a Analyzers,
+            filepath: &Path,
+            contents: &[u8],
+            limit: usize,
+        ) -> Option<(&'a Language, Detection)> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            FullDetector.detect(analyzers, filepath, contents, limit)
+        }
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let repos = ["first", "second"].map(|name| dir.path().join(name));
+    for (repo, script) in repos.iter().zip(["a.py", "b.py"]) {
+        fs::create_dir(repo).unwrap();
+        git(repo, &["init", "-q"]);
+        fs::write(repo.join("lib.rs"), b"pub fn f() {}\n").unwrap();
+        fs::write(repo.join(script), script.as_bytes()).unwrap();
+        git(repo, &["add", "."]);
+        git(repo, &["commit", "-q", "-m", "repo"]);
+    }
+
+    let cache = Arc::new(BlobCache::with_capacity(8));
+    let count = Arc::new(AtomicUsize::new(0));
+    for repo in &repos {
+        let gengo = Builder::new(repo)
+            .cache(Arc::clone(&cache))
+            .detector(Box::new(Counting(Arc::clone(&count))))
+            .build()
+            .unwrap();
+        let results = gengo.analyze("HEAD").unwrap();
+        assert_eq!(results.get("lib.rs").unwrap().language().name(), "Rust");
+    }
+    assert_eq!(
+        count.load(Ordering::SeqCst),
+        3,
+        "lib.rs should only be read once"
+    );
+    assert_eq!((cache.hits(), cache.misses()), (1, 3));
+    assert_eq!(cache.len(), 3);
+}
+
+/// Analyzes a repository of Rust files that the heuristics classify
+/// differently, with the given `.gitattributes`. Returns the language,
+/// detection source, and the generated, documentation, vendored, and
+/// detectable flags of each file, sorted by path.
+fn analyze_with_attributes(attributes: &str) -> Vec<(String, Detection, [bool; 4])> {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    for path in ["dist", "docs", "node_modules", "src"] {
+        fs::create_dir_all(repo.join(path)).unwrap();
+        fs::write(repo.join(path).join("lib.rs"), b"pub fn f() {}\n").unwrap();
+    }
+    fs::write(repo.join(".gitattributes"), attributes).unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "attributes"]);
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let mut entries: Vec<_> = results
+        .iter()
+        .filter(|(path, _)| path.extension().is_some_and(|ext| ext == "rs"))
+        .map(|(path, entry)| {
+            let flags = [
+                entry.generated(),
+                entry.documentation(),
+                entry.vendored(),
+                entry.detectable(),
+            ];
+            let language = entry.language().name().to_string();
+            (path.into_owned(), language, entry.detection_source(), flags)
+        })
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+        .into_iter()
+        .map(|(_, language, detection, flags)| (language, detection, flags))
+        .collect()
+}
+
+#[test]
+fn test_boolean_attribute_states() {
+    // NOTE Heuristics for dist/, docs/, node_modules/, and src/, in order.
+    let attributes: [(&str, [bool; 4]); 4] = [
+        ("gengo-generated", [true, false, false, false]),
+        ("gengo-documentation", [false, true, false, false]),
+        ("gengo-vendored", [false, false, true, false]),
+        ("gengo-detectable", [false, false, false, true]),
+    ];
+    for (index, (name, heuristics)) in attributes.into_iter().enumerate() {
+        let states = [
+            (String::new(), heuristics),
+            (format!("*.rs {name}\n"), [true; 4]),
+            (format!("*.rs {name}=true\n"), [true; 4]),
+            (format!("*.rs -{name}\n"), [false; 4]),
+            (format!("*.rs {name}=false\n"), [false; 4]),
+        ];
+        for (attributes, expected) in states {
+            let actual: Vec<_> = analyze_with_attributes(&attributes)
+                .into_iter()
+                .map(|(_, _, flags)| flags[index])
+                .collect();
+            assert_eq!(actual, expected, "{name} with {attributes:?}");
+        }
+    }
+}
+
+#[test]
+fn test_language_attribute_states() {
+    let states = [
+        ("", "Rust", Detection::Extension),
+        (
+            "*.rs gengo-language=Plain-Text\n",
+            "Plain Text",
+            Detection::Attribute,
+        ),
+        ("*.rs -gengo-language\n", "Rust", Detection::Extension),
+    ];
+    for (attributes, language, detection) in states {
+        for entry in analyze_with_attributes(attributes) {
+            assert_eq!(
+                (entry.0.as_str(), entry.1),
+                (language, detection),
+                "{attributes:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_language_attribute_aliases() {
+    for (alias, language) in [("c++", "C++"), ("golang", "Go"), ("JS", "JavaScript")] {
+        for entry in analyze_with_attributes(&format!("*.rs gengo-language={alias}\n")) {
+            assert_eq!(
+                (entry.0.as_str(), entry.1),
+                (language, Detection::Attribute),
+                "{alias}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_entry_attributes() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    for path in ["main.rs", "lib.rs", "build.rs"] {
+        fs::write(repo.join(path), b"pub fn f() {}\n").unwrap();
+    }
+    fs::write(
+        repo.join(".gitattributes"),
+        b"main.rs gengo-detectable=false gengo-language=Plain-Text\n\
+          lib.rs linguist-generated\n",
+    )
+    .unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "attributes"]);
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let main = results.get("main.rs").unwrap().attributes();
+    assert_eq!(main.detectable(), Assignment::Unset);
+    assert_eq!(main.language(), Assignment::Set);
+    assert_eq!(main.generated(), Assignment::Unspecified);
+    let lib = results.get("lib.rs").unwrap().attributes();
+    assert_eq!(lib.generated(), Assignment::Set);
+    assert_eq!(lib.detectable(), Assignment::Unspecified);
+    assert_eq!(
+        results.get("build.rs").unwrap().attributes(),
+        &AttributeState::default()
+    );
+}
+
+#[test]
+fn test_linguist_attributes() {
+    let entries = analyze_with_attributes(
+        "src/* linguist-generated\n\
+         docs/* linguist-documentation=false\n\
+         node_modules/* linguist-vendored gengo-vendored=false\n\
+         dist/* linguist-language=Plain-Text -linguist-generated\n",
+    );
+    let flags: Vec<_> = entries
+        .iter()
+        .map(|(_, _, flags)| flags[..3].to_vec())
+        .collect();
+    assert_eq!(
+        flags,
+        vec![
+            vec![false, false, false],
+            vec![false, false, false],
+            vec![false, false, false],
+            vec![true, false, false],
+        ],
+        "gengo attributes should take precedence over linguist attributes"
+    );
+    assert_eq!(
+        (entries[0].0.as_str(), entries[0].1),
+        ("Plain Text", Detection::Attribute)
+    );
+}
+
+#[test]
+fn test_unresolved_overrides() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(repo.join("main.py"), b"print('hello')\n").unwrap();
+    fs::write(repo.join("lib.rs"), b"pub fn f() {}\n").unwrap();
+    fs::write(
+        repo.join(".gitattributes"),
+        b"*.py gengo-language=Pyton\n*.rs gengo-language=Rust\n",
+    )
+    .unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "overrides"]);
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let unresolved = results.unresolved_overrides();
+    assert_eq!(unresolved.len(), 1);
+    assert_eq!(unresolved[0].path(), Path::new("main.py"));
+    assert_eq!(unresolved[0].name(), "Pyton");
+    assert_eq!(unresolved[0].suggestion(), Some("Python"));
+
+    // NOTE The unresolved override is ignored.
+    let (_, entry) = results
+        .iter()
+        .find(|(path, _)| path.as_ref() == Path::new("main.py"))
+        .unwrap();
+    assert_eq!(entry.language().name(), "Python");
+}
+
+#[test]
+fn test_bare_repository() {
+    let dir = tempfile::tempdir().unwrap();
+    let work = dir.path().join("work");
+    let bare = dir.path().join("bare.git");
+    fs::create_dir_all(&work).unwrap();
+    git(&work, &["init", "-q"]);
+    fs::write(work.join("main.rs"), b"fn main() {}\n").unwrap();
+    fs::write(work.join("build.rs"), b"fn main() {}\n").unwrap();
+    fs::write(work.join(".gitattributes"), b"build.rs gengo-generated\n").unwrap();
+    git(&work, &["add", "."]);
+    git(&work, &["commit", "-q", "-m", "bare"]);
+    git(
+        dir.path(),
+        &[
+            "clone",
+            "-q",
+            "--mirror",
+            work.to_str().unwrap(),
+            bare.to_str().unwrap(),
+        ],
+    );
+
+    let gengo = Builder::new(&bare).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let main = results.get("main.rs").unwrap();
+    assert_eq!(main.language().name(), "Rust");
+    assert!(!main.generated());
+    assert!(results.get("build.rs").unwrap().generated());
+
+    #[cfg(feature = "git")]
+    {
+        let git = Git::new(&bare, "HEAD").unwrap();
+        assert_eq!(git.files().unwrap().count(), 3);
+    }
+}
+
+#[test]
+fn test_analyze_revisions() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(repo.join("first.rs"), b"fn first() {}\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "first"]);
+    fs::write(repo.join("second.rs"), b"fn second() {}\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "second"]);
+
+    let cache = Arc::new(BlobCache::new());
+    let gengo = Builder::new(repo)
+        .cache(Arc::clone(&cache))
+        .build()
+        .unwrap();
+    let analyses = gengo.analyze_revisions(&["HEAD~1", "HEAD"]).unwrap();
+    let counts: Vec<_> = analyses
+        .iter()
+        .map(|(rev, analysis)| (rev.as_str(), analysis.len()))
+        .collect();
+    assert_eq!(counts, vec![("HEAD~1", 1), ("HEAD", 2)]);
+    assert_eq!(cache.len(), 2, "first.rs should only be cached once");
+
+    assert!(gengo
+        .analyze_revisions(&["HEAD", "does-not-exist"])
+        .is_err());
+}
+
+#[test]
+fn test_blame() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(repo.join("lib.rs"), b"fn a() {}\nfn b() {}\nfn c() {}\n").unwrap();
+    git(repo, &["add", "."]);
+    git(
+        repo,
+        &[
+            "commit",
+            "-q",
+            "-m",
+            "a",
+            "--author",
+            "Alice <alice@example.com>",
+        ],
+    );
+    fs::write(
+        repo.join("lib.rs"),
+        b"fn a() {}\nfn x() {}\nfn c() {}\nfn d() {}\n",
+    )
+    .unwrap();
+    fs::create_dir_all(repo.join("node_modules")).unwrap();
+    fs::write(repo.join("node_modules/dep.js"), b"module.exports = 1;\n").unwrap();
+    git(repo, &["add", "."]);
+    git(
+        repo,
+        &[
+            "commit",
+            "-q",
+            "-m",
+            "b",
+            "--author",
+            "Bob <bob@example.com>",
+        ],
+    );
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let authors = gengo.blame("HEAD", &AtomicBool::new(false)).unwrap();
+    let mut lines: Vec<_> = authors
+        .iter()
+        .flat_map(|(author, summary)| {
+            summary.iter().map(move |(language, lines)| {
+                (
+                    author.name().to_string(),
+                    language.name().to_string(),
+                    *lines,
+                )
+            })
+        })
+        .collect();
+    lines.sort();
+    assert_eq!(
+        lines,
+        vec![
+            ("Alice".to_string(), "Rust".to_string(), 2),
+            ("Bob".to_string(), "Rust".to_string(), 2),
+        ]
+    );
+
+    let tree = rev_parse(repo, "HEAD^{tree}");
+    assert!(gengo.blame(&tree, &AtomicBool::new(false)).is_err());
+}
+
+#[test]
+fn test_revision_forms() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q", "-b", "main"]);
+    fs::write(repo.join("first.rs"), b"fn first() {}\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "first"]);
+    git(repo, &["tag", "v1"]);
+    git(repo, &["branch", "old"]);
+    fs::write(repo.join("second.rs"), b"fn second() {}\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "second"]);
+
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=7", "HEAD~1"])
+        .current_dir(repo)
+        .output()
+        .unwrap();
+    let short_sha = String::from_utf8(output.stdout).unwrap();
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let paths = |rev: &str| {
+        let results = gengo.analyze(rev).unwrap();
+        let mut paths: Vec<_> = results.iter().map(|(path, _)| path.into_owned()).collect();
+        paths.sort();
+        paths
+    };
+    let first = vec![PathBuf::from("first.rs")];
+    let both = vec![PathBuf::from("first.rs"), PathBuf::from("second.rs")];
+    assert_eq!(paths("HEAD"), both);
+    assert_eq!(paths("main"), both);
+    assert_eq!(paths("HEAD~1"), first);
+    assert_eq!(paths("v1"), first);
+    assert_eq!(paths("old"), first);
+    assert_eq!(paths(short_sha.trim()), first);
+}
+
+#[test]
+fn test_bad_revision() {
+    let gengo = Builder::new(ROOT).build().unwrap();
+    let err = gengo.analyze("does-not-exist").unwrap_err();
+    let err = err.downcast_ref::<gengo::Error>().unwrap();
+    assert!(matches!(err.kind(), gengo::ErrorKind::BadRevision));
+    assert!(err.to_string().contains("does-not-exist"));
+}
+
+#[test]
+fn test_resolve() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q", "-b", "main"]);
+    fs::write(repo.join("main.rs"), b"fn main() {}\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "repo"]);
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let err = gengo.resolve("mian").unwrap_err();
+    let err = err.downcast_ref::<gengo::Error>().unwrap();
+    assert!(matches!(err.kind(), gengo::ErrorKind::BadRevision));
+    assert!(err.to_string().contains("mian"));
+
+    let id = gengo.resolve("main").unwrap();
+    let resolved = gengo.analyze(&id.to_string()).unwrap();
+    let analyzed = gengo.analyze("main").unwrap();
+    assert_eq!(resolved.revision(), analyzed.revision());
+    assert_eq!(resolved.commit(), analyzed.commit());
+    assert_eq!(resolved.commit(), Some(id));
+}
+
+#[test]
+fn test_empty_repository() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q", "-b", "main"]);
+
+    let gengo = Builder::new(repo).build().unwrap();
+    for rev in ["HEAD", "main"] {
+        let results = gengo.analyze(rev).unwrap();
+        assert!(results.is_empty());
+        assert!(results.commit().is_none());
+    }
+    let err = gengo.analyze("v1").unwrap_err();
+    let err = err.downcast_ref::<gengo::Error>().unwrap();
+    assert!(matches!(err.kind(), gengo::ErrorKind::NoCommits));
+
+    git(repo, &["commit", "-q", "--allow-empty", "-m", "empty"]);
+    let results = gengo.analyze("HEAD").unwrap();
+    assert!(results.is_empty());
+    assert!(results.commit().is_some());
+}
+
+#[test]
+fn test_skipped_missing_object() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(repo.join("main.rs"), b"fn main() {}\n").unwrap();
+    fs::write(repo.join("lib.rs"), b"pub fn f() {}\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "missing"]);
+
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD:lib.rs"])
+        .current_dir(repo)
+        .output()
+        .unwrap();
+    let id = String::from_utf8(output.stdout).unwrap();
+    let (prefix, rest) = id.trim().split_at(2);
+    fs::remove_file(repo.join(".git/objects").join(prefix).join(rest)).unwrap();
+
+    for detector in [
+        Box::new(FullDetector) as Box<dyn Detector>,
+        Box::new(ExtensionDetector),
+    ] {
+        let gengo = Builder::new(repo).detector(detector).build().unwrap();
+        let results = gengo.analyze("HEAD").unwrap();
+        assert!(results.get("main.rs").is_some());
+        assert!(results.get("lib.rs").is_none());
+        assert_eq!(
+            results.skipped(),
+            vec![("lib.rs".into(), SkipReason::ObjectMissing)]
+        );
+    }
+}
+
+#[test]
+fn test_skipped_interrupted() {
+    let gengo = Builder::new(ROOT).threads(1).build().unwrap();
+    let interrupt = AtomicBool::new(false);
+    let results = gengo
+        .analyze_with_progress("HEAD", &interrupt, |_, _| {
+            interrupt.store(true, std::sync::atomic::Ordering::Relaxed)
+        })
+        .unwrap();
+    let interrupted: Vec<_> = results
+        .skipped()
+        .into_iter()
+        .filter(|(_, reason)| *reason != SkipReason::Symlink)
+        .collect();
+    assert!(!interrupted.is_empty());
+    assert!(interrupted
+        .iter()
+        .all(|(_, reason)| *reason == SkipReason::Interrupted));
+    assert!(results.len() <= 1);
+}
+
+#[test]
+fn test_linguist_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(repo.join("main.rs"), b"fn main() {\n    f();\n}\n").unwrap();
+    fs::write(repo.join("lib.rs"), b"fn f() {}\n").unwrap();
+    fs::write(repo.join("index.js"), b"f();\n").unwrap();
+    fs::write(repo.join("README.md"), b"# Project\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "linguist"]);
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&gengo::report::to_linguist_json(&results)).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "Rust": {"size": 33, "percentage": "86.84", "lines": 4},
+            "JavaScript": {"size": 5, "percentage": "13.16", "lines": 1},
+        })
+    );
+
+    let summary = gengo::report::summary_to_linguist_json(&results.summary());
+    assert_eq!(
+        summary,
+        r#"{"Rust":{"size":33,"percentage":"86.84"},"JavaScript":{"size":5,"percentage":"13.16"}}"#
+    );
+}
+
+#[test]
+fn test_breakdown() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::create_dir_all(repo.join("dist")).unwrap();
+    fs::write(repo.join("main.rs"), b"fn main() {}\n").unwrap();
+    fs::write(repo.join("dist/app, min.js"), b"f();\n").unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "breakdown"]);
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let results = gengo.analyze("HEAD").unwrap();
+    let records = gengo::report::breakdown(&results);
+    let paths: Vec<_> = records.iter().map(|r| r.path.clone()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("dist").join("app, min.js"),
+            PathBuf::from("main.rs")
+        ]
+    );
+    assert!(records[0].generated && !records[0].detectable);
+    assert_eq!(records[1].language, "Rust");
+    assert_eq!(records[1].size, 13);
+
+    assert_eq!(
+        gengo::report::breakdown_to_csv(&records),
+        "path,language,size,generated,vendored,documentation,detectable\n\
+         \"dist/app, min.js\",JavaScript,5,true,false,false,false\n\
+         main.rs,Rust,13,false,false,false,true\n"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn test_symlinks_are_skipped() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = dir.path();
+    git(repo, &["init", "-q"]);
+    fs::write(repo.join("main.rs"), b"fn main() {}\n").unwrap();
+    std::os::unix::fs::symlink("main.rs", repo.join("link.rs")).unwrap();
+    git(repo, &["add", "."]);
+    git(repo, &["commit", "-q", "-m", "symlink"]);
+
+    let gengo = Builder::new(repo).build().unwrap();
+    let mut reported = Vec::new();
+    let results = gengo
+        .analyze_with_progress("HEAD", &AtomicBool::new(false), |processed, total| {
+            reported.push((processed, total))
+        })
+        .unwrap();
+    assert_eq!(reported.last(), Some(&(1, 1)));
+    assert!(results.get("main.rs").is_some());
+    assert!(results.get("link.rs").is_none());
+    assert_eq!(
+        results.skipped(),
+        vec![("link.rs".into(), SkipReason::Symlink)]
+    );
+}
+
+#[test]
+fn test_max_submodule_depth() {
+    let dir = tempfile::tempdir().unwrap();
+    let repo = repo_with_nested_submodule(dir.path());
+
+    let paths = |depth| {
+        let gengo = Builder::new(&repo)
+            .max_submodule_depth(depth)
+            .build()
+            .unwrap();
+        let results = gengo.analyze("HEAD").unwrap();
+        let mut paths: Vec<_> = results
+            .iter()
+            .map(|(path, _)| path.into_owned())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+            .collect();
+        paths.sort();
+        (paths, results.skipped())
+    };
+
+    let (files, skipped) = paths(2);
+    assert_eq!(
+        files,
+        vec![
+            PathBuf::from("main.rs"),
+            PathBuf::from("sub/lib.rs"),
+            PathBuf::from("sub/nested/nested.rs"),
+        ]
+    );
+    assert!(skipped.is_empty());
+
+    let (files, skipped) = paths(1);
+    assert_eq!(
+        files,
+        vec![PathBuf::from("main.rs"), PathBuf::from("sub/lib.rs")]
+    );
+    assert_eq!(
+        skipped,
+        vec![("sub/nested".into(), SkipReason::MaxSubmoduleDepth)]
+    );
+
+    let (files, skipped) = paths(0);
+    assert_eq!(files, vec![PathBuf::from("main.rs")]);
+    assert_eq!(skipped, vec![("sub".into(), SkipReason::MaxSubmoduleDepth)]);
+}